@@ -0,0 +1,87 @@
+// src/cache.rs
+
+//!
+//! Кеш свойств торрентов между запусками: хеш → комментарий.
+//! Комментарий неизменен для торрента с тем же хешем, поэтому повторно
+//! запрашивать свойства у qBittorrent для уже известных торрентов не нужно.
+//! Там же хранятся последние известные сведения о теме с трекера.
+//!
+
+use crate::topic_info::TopicInfo;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CachedProperties {
+    pub comment: String,
+    /// Последние известные сведения о теме (для сравнения при обновлении раздачи)
+    #[serde(default)]
+    pub topic: Option<TopicInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PropertiesCache {
+    entries: HashMap<String, CachedProperties>,
+}
+
+impl PropertiesCache {
+    /// Загружает кеш. Отсутствующий или поврежденный файл дает пустой кеш.
+    pub fn load(path: &Path) -> Self {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(_) => {
+                log::debug!("Кеш свойств {} не найден, будет создан.", path.display());
+                return Self::default();
+            }
+        };
+
+        serde_json::from_slice(&data).unwrap_or_else(|e| {
+            log::warn!(
+                "⚠️ Кеш свойств {} поврежден и будет пересоздан: {}",
+                path.display(),
+                e
+            );
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("❌ Не удалось создать папку {}", dir.display()))?;
+        }
+        let data = serde_json::to_vec(self)?;
+        std::fs::write(path, data)
+            .with_context(|| format!("❌ Не удалось сохранить кеш свойств {}", path.display()))
+    }
+
+    pub fn get(&self, hash: &str) -> Option<&CachedProperties> {
+        self.entries.get(hash)
+    }
+
+    pub fn insert(&mut self, hash: String, properties: CachedProperties) {
+        self.entries.insert(hash, properties);
+    }
+
+    /// Запоминает сведения о теме для уже известного торрента
+    pub fn set_topic(&mut self, hash: &str, topic: TopicInfo) {
+        if let Some(entry) = self.entries.get_mut(hash) {
+            entry.topic = Some(topic);
+        }
+    }
+
+    /// Удаляет записи о торрентах, которых больше нет в клиенте
+    pub fn retain_hashes(&mut self, present: &HashSet<String>) {
+        self.entries.retain(|hash, _| present.contains(hash));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,212 @@
+// src/connection.rs
+
+//!
+//! Подключение к qBittorrent WebUI: собственный CA или отключение проверки сертификата,
+//! basic auth и дополнительные заголовки для обратного прокси, вход без пароля
+//! (bypass для localhost) и понятные сообщения при отказе во входе
+//!
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use qbit_rs::{model::Credential, Qbit};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::{Certificate, Client, StatusCode};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Учетные данные для basic auth обратного прокси (nginx и т.п.) перед WebUI
+#[derive(Deserialize, Debug, Clone)]
+pub struct BasicAuth {
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+}
+
+/// Настройки соединения с WebUI (поля секции `[qbit]` помимо адреса и логина)
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ConnectionConfig {
+    /// PEM-файл с сертификатом CA для самоподписанного сертификата WebUI
+    pub ca_cert: Option<String>,
+    /// Не проверять сертификат WebUI вообще (только для доверенной сети)
+    pub accept_invalid_certs: bool,
+    /// Basic auth для обратного прокси
+    pub basic_auth: Option<BasicAuth>,
+    /// Дополнительные заголовки для каждого запроса к WebUI
+    pub headers: BTreeMap<String, String>,
+    /// Таймаут запроса к WebUI, секунды
+    pub timeout_secs: Option<u64>,
+}
+
+/// Почему не удалось подключиться к WebUI
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConnectError {
+    #[error("qBittorrent отклонил логин или пароль (qbit.username / qbit.password)")]
+    LoginRejected,
+    #[error("IP заблокирован qBittorrent после неудачных попыток входа; подождите или снимите блокировку в настройках WebUI")]
+    IpBanned,
+    #[error("qBittorrent требует вход: задайте qbit.username и qbit.password или включите обход авторизации для localhost")]
+    LoginRequired,
+    #[error("обратный прокси требует авторизацию (HTTP 401): проверьте qbit.basic_auth")]
+    ProxyAuthRequired,
+    #[error("неожиданный ответ WebUI: HTTP {0}")]
+    UnexpectedStatus(u16),
+}
+
+/// Создает HTTP-клиент для WebUI с учетом настроек TLS и обратного прокси
+pub fn build_client(config: &ConnectionConfig) -> Result<Client> {
+    let mut builder = Client::builder();
+
+    if let Some(path) = &config.ca_cert {
+        let pem = std::fs::read(path)
+            .with_context(|| format!("❌ Не удалось прочитать сертификат CA {}", path))?;
+        let certificate = Certificate::from_pem(&pem)
+            .with_context(|| format!("❌ Некорректный сертификат CA {}", path))?;
+        builder = builder.add_root_certificate(certificate);
+    }
+    if config.accept_invalid_certs {
+        log::warn!("⚠️ Проверка сертификата qBittorrent WebUI отключена");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    if let Some(secs) = config.timeout_secs {
+        builder = builder.timeout(Duration::from_secs(secs));
+    }
+
+    builder
+        .default_headers(default_headers(config)?)
+        .build()
+        .context("❌ Не удалось создать HTTP-клиент для qBittorrent")
+}
+
+fn default_headers(config: &ConnectionConfig) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+
+    for (name, value) in &config.headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .with_context(|| format!("❌ Некорректное имя заголовка '{}'", name))?;
+        let value = HeaderValue::from_str(value)
+            .with_context(|| format!("❌ Некорректное значение заголовка '{}'", name))?;
+        headers.insert(name, value);
+    }
+
+    if let Some(auth) = &config.basic_auth {
+        let encoded = STANDARD.encode(format!("{}:{}", auth.username, auth.password));
+        let mut value = HeaderValue::from_str(&format!("Basic {}", encoded))?;
+        value.set_sensitive(true);
+        headers.insert(AUTHORIZATION, value);
+    }
+
+    Ok(headers)
+}
+
+/// Проверяет доступ к WebUI и возвращает готовый клиент qBittorrent.
+///
+/// Без логина клиент работает только при включенном в qBittorrent обходе авторизации.
+pub async fn connect(
+    url: &str,
+    username: &str,
+    password: &str,
+    config: &ConnectionConfig,
+) -> Result<Qbit> {
+    let client = build_client(config)?;
+    let base = url.trim_end_matches('/');
+
+    if username.is_empty() {
+        let response = client
+            .get(format!("{}/api/v2/app/version", base))
+            .send()
+            .await
+            .with_context(|| format!("❌ qBittorrent по адресу {} недоступен", url))?;
+        check_status(response.status(), ConnectError::LoginRequired)?;
+
+        // qbit-rs требует cookie или логин; при обходе авторизации cookie не проверяется
+        return Ok(Qbit::builder()
+            .endpoint(url)
+            .client(client)
+            .cookie("SID=")
+            .build());
+    }
+
+    let response = client
+        .post(format!("{}/api/v2/auth/login", base))
+        .header(reqwest::header::REFERER, base)
+        .form(&[("username", username), ("password", password)])
+        .send()
+        .await
+        .with_context(|| format!("❌ qBittorrent по адресу {} недоступен", url))?;
+    check_status(response.status(), ConnectError::IpBanned)?;
+    let body = response.text().await.unwrap_or_default();
+    check_login_body(&body)?;
+
+    Ok(Qbit::new_with_client(
+        url,
+        Credential::new(username, password),
+        client,
+    ))
+}
+
+/// Разбирает статус ответа; `forbidden` — что означает 403 для этого запроса
+fn check_status(status: StatusCode, forbidden: ConnectError) -> Result<(), ConnectError> {
+    match status {
+        s if s.is_success() => Ok(()),
+        StatusCode::UNAUTHORIZED | StatusCode::PROXY_AUTHENTICATION_REQUIRED => {
+            Err(ConnectError::ProxyAuthRequired)
+        }
+        StatusCode::FORBIDDEN => Err(forbidden),
+        s => Err(ConnectError::UnexpectedStatus(s.as_u16())),
+    }
+}
+
+/// qBittorrent отвечает на вход телом `Ok.` или `Fails.` со статусом 200
+fn check_login_body(body: &str) -> Result<(), ConnectError> {
+    match body.trim() {
+        "Fails." => Err(ConnectError::LoginRejected),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_status() {
+        assert_eq!(check_status(StatusCode::OK, ConnectError::IpBanned), Ok(()));
+        assert_eq!(
+            check_status(StatusCode::FORBIDDEN, ConnectError::IpBanned),
+            Err(ConnectError::IpBanned)
+        );
+        assert_eq!(
+            check_status(StatusCode::UNAUTHORIZED, ConnectError::LoginRequired),
+            Err(ConnectError::ProxyAuthRequired)
+        );
+        assert_eq!(
+            check_status(StatusCode::BAD_GATEWAY, ConnectError::LoginRequired),
+            Err(ConnectError::UnexpectedStatus(502))
+        );
+        assert_eq!(check_login_body("Fails."), Err(ConnectError::LoginRejected));
+        assert_eq!(check_login_body("Ok."), Ok(()));
+    }
+
+    #[test]
+    fn test_default_headers() {
+        let config = ConnectionConfig {
+            basic_auth: Some(BasicAuth {
+                username: "user".into(),
+                password: "pass".into(),
+            }),
+            headers: BTreeMap::from([("X-Forwarded-User".into(), "keeper".into())]),
+            ..Default::default()
+        };
+        let headers = default_headers(&config).unwrap();
+        assert_eq!(headers[AUTHORIZATION], "Basic dXNlcjpwYXNz");
+        assert_eq!(headers["x-forwarded-user"], "keeper");
+
+        let invalid = ConnectionConfig {
+            headers: BTreeMap::from([("bad header".into(), "x".into())]),
+            ..Default::default()
+        };
+        assert!(default_headers(&invalid).is_err());
+    }
+}
@@ -0,0 +1,342 @@
+// src/control.rs
+
+//!
+//! HTTP API управления для фонового режима: запуск проверки по запросу, отчет о последнем
+//! запуске, список проверенных торрентов и простая страница состояния. Доступ — по токену.
+//!
+
+use crate::report::RunReport;
+use crate::topic_id::TopicId;
+use crate::torrent::Torrent;
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// Настройки API управления (секция `[control]` в config.toml)
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ControlConfig {
+    /// Адрес HTTP-сервера, например `127.0.0.1:9188` (только в режиме `daemon`)
+    pub listen: Option<String>,
+    /// Токен доступа: заголовок `Authorization: Bearer <токен>`
+    pub token: Option<String>,
+}
+
+/// Торрент из последнего запуска в ответе `GET /torrents`
+#[derive(Serialize, Debug, Clone)]
+pub struct TorrentStatus {
+    pub tracker: String,
+    pub name: String,
+    pub hash: String,
+    pub topic_id: Option<TopicId>,
+    pub seeders: u32,
+    pub leechers: u32,
+    pub state: String,
+    pub category: String,
+    pub size: u64,
+}
+
+impl TorrentStatus {
+    pub fn new(tracker: &str, torrent: &Torrent) -> Self {
+        TorrentStatus {
+            tracker: tracker.to_string(),
+            name: torrent.name.clone(),
+            hash: torrent.torrent_hash.clone(),
+            topic_id: torrent.torrent_id,
+            seeders: torrent.seeders,
+            leechers: torrent.leechers,
+            state: torrent.state.clone(),
+            category: torrent.category.clone(),
+            size: torrent.size,
+        }
+    }
+}
+
+/// Ответ `GET /status`
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct Status {
+    /// Идет ли проверка прямо сейчас
+    pub running: bool,
+    /// Запрошен ли внеочередной запуск
+    pub run_requested: bool,
+    /// Ошибка последнего запуска, если он не завершился
+    pub last_error: Option<String>,
+    pub last_report: Option<RunReport>,
+}
+
+/// Общее состояние фонового цикла и HTTP-сервера
+#[derive(Default)]
+pub struct Control {
+    status: Mutex<Status>,
+    torrents: Mutex<Vec<TorrentStatus>>,
+    trigger: Notify,
+}
+
+impl Control {
+    /// Ждет внеочередного запуска, запрошенного через `POST /run`
+    pub async fn run_requested(&self) {
+        self.trigger.notified().await;
+    }
+
+    pub fn request_run(&self) {
+        self.lock_status().run_requested = true;
+        self.trigger.notify_one();
+    }
+
+    pub fn run_started(&self) {
+        let mut status = self.lock_status();
+        status.running = true;
+        status.run_requested = false;
+    }
+
+    pub fn run_finished(&self, report: RunReport, torrents: Vec<TorrentStatus>) {
+        let mut status = self.lock_status();
+        status.running = false;
+        status.last_error = None;
+        status.last_report = Some(report);
+        *self.torrents.lock().unwrap_or_else(|e| e.into_inner()) = torrents;
+    }
+
+    pub fn run_failed(&self, error: String) {
+        let mut status = self.lock_status();
+        status.running = false;
+        status.last_error = Some(error);
+    }
+
+    pub fn status(&self) -> Status {
+        self.lock_status().clone()
+    }
+
+    pub fn torrents(&self) -> Vec<TorrentStatus> {
+        self.torrents
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn lock_status(&self) -> std::sync::MutexGuard<'_, Status> {
+        self.status.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[derive(Clone)]
+struct AppState {
+    control: Arc<Control>,
+    token: Arc<str>,
+}
+
+/// Запускает HTTP-сервер API управления в фоне
+pub async fn serve(config: &ControlConfig, control: Arc<Control>) -> Result<()> {
+    let Some(listen) = &config.listen else {
+        return Ok(());
+    };
+    let token = config
+        .token
+        .as_deref()
+        .filter(|t| !t.is_empty())
+        .context("❌ Для API управления нужно задать control.token")?;
+
+    let listener = tokio::net::TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("❌ Не удалось открыть адрес API управления {}", listen))?;
+    log::info!("🕹️ API управления доступно по адресу http://{}/", listen);
+
+    let app = router(AppState {
+        control,
+        token: token.into(),
+    });
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            log::error!("❌ Сервер API управления остановлен: {}", e);
+        }
+    });
+    Ok(())
+}
+
+fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/run", post(run))
+        .route("/status", get(status))
+        .route("/torrents", get(torrents))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authorize))
+        // Страница не содержит данных: токен она запрашивает сама и передает в заголовке
+        .route("/", get(index))
+        .with_state(state)
+}
+
+async fn authorize(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if request_token(&request).is_some_and(|token| token_matches(token, &state.token)) {
+        next.run(request).await
+    } else {
+        (StatusCode::UNAUTHORIZED, "неверный или отсутствующий токен").into_response()
+    }
+}
+
+/// Токен из заголовка `Authorization: Bearer ...`. В адресе токен не принимается: оттуда он
+/// попадает в логи и историю браузера.
+fn request_token(request: &Request) -> Option<&str> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+/// Сравнивает токены за время, которое не зависит от места первого несовпадения
+fn token_matches(given: &str, expected: &str) -> bool {
+    let (given, expected) = (given.as_bytes(), expected.as_bytes());
+    given.len() == expected.len()
+        && given
+            .iter()
+            .zip(expected)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn run(State(state): State<AppState>) -> impl IntoResponse {
+    log::info!("🕹️ Запрошен внеочередной запуск через API");
+    state.control.request_run();
+    (StatusCode::ACCEPTED, Json(state.control.status()))
+}
+
+async fn status(State(state): State<AppState>) -> Json<Status> {
+    Json(state.control.status())
+}
+
+async fn torrents(State(state): State<AppState>) -> Json<Vec<TorrentStatus>> {
+    Json(state.control.torrents())
+}
+
+async fn index() -> Html<&'static str> {
+    Html(INDEX_HTML)
+}
+
+const INDEX_HTML: &str = r#"<!DOCTYPE html>
+<html lang="ru">
+<head>
+<meta charset="utf-8">
+<title>tracker-updater</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #ccc; padding: 2px 8px; text-align: left; }
+</style>
+</head>
+<body>
+<h1>tracker-updater</h1>
+<p id="state">Загрузка...</p>
+<button id="run">Запустить проверку</button>
+<h2>Последний запуск</h2>
+<table id="report"></table>
+<h2>Торренты</h2>
+<table id="torrents"></table>
+<script>
+let token = sessionStorage.getItem("token") || "";
+const headers = () => ({ "Authorization": "Bearer " + token });
+async function api(path, options = {}) {
+  for (;;) {
+    if (!token) {
+      token = prompt("Токен API управления (control.token)") || "";
+      sessionStorage.setItem("token", token);
+    }
+    const response = await fetch(path, { ...options, headers: headers() });
+    if (response.status !== 401) return response;
+    token = "";
+    sessionStorage.removeItem("token");
+  }
+}
+const cell = (row, text, tag = "td") => {
+  const el = document.createElement(tag);
+  el.textContent = text ?? "";
+  row.appendChild(el);
+};
+const fill = (table, columns, rows) => {
+  table.replaceChildren();
+  const head = table.insertRow();
+  columns.forEach(c => cell(head, c, "th"));
+  rows.forEach(r => { const row = table.insertRow(); r.forEach(v => cell(row, v)); });
+};
+async function refresh() {
+  const status = await (await api("status")).json();
+  const report = status.last_report;
+  document.getElementById("state").textContent =
+    (status.running ? "Идет проверка. " : "") +
+    (report ? "Последний запуск: " + new Date(report.started_at * 1000).toLocaleString() +
+      ", проверено: " + report.checked : "Запусков еще не было") +
+    (status.last_error ? ". Ошибка: " + status.last_error : "");
+  fill(document.getElementById("report"), ["Действие", "Торрент", "ID темы", "Ошибка"],
+    (report ? report.entries : []).map(e => [e.action, e.name, e.topic_id, e.error]));
+  const torrents = await (await api("torrents")).json();
+  fill(document.getElementById("torrents"), ["Трекер", "Торрент", "ID темы", "Сиды", "Личи", "Состояние"],
+    torrents.map(t => [t.tracker, t.name, t.topic_id, t.seeders, t.leechers, t.state]));
+}
+document.getElementById("run").onclick = async () => {
+  await api("run", { method: "POST" });
+  refresh();
+};
+refresh();
+setInterval(refresh, 10000);
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn request(uri: &str, authorization: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri(uri);
+        if let Some(value) = authorization {
+            builder = builder.header(header::AUTHORIZATION, value);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn test_request_token() {
+        assert_eq!(
+            request_token(&request("/status", Some("Bearer secret"))),
+            Some("secret")
+        );
+        assert_eq!(request_token(&request("/?token=secret", None)), None);
+        assert_eq!(request_token(&request("/status", None)), None);
+        assert_eq!(request_token(&request("/status", Some("Basic abc"))), None);
+    }
+
+    #[test]
+    fn test_token_matches() {
+        assert!(token_matches("secret", "secret"));
+        assert!(!token_matches("secreT", "secret"));
+        assert!(!token_matches("secret1", "secret"));
+        assert!(!token_matches("", "secret"));
+    }
+
+    #[test]
+    fn test_run_request_lifecycle() {
+        let control = Control::default();
+        control.request_run();
+        assert!(control.status().run_requested);
+
+        control.run_started();
+        let status = control.status();
+        assert!(status.running && !status.run_requested);
+
+        control.run_failed("нет связи".into());
+        let status = control.status();
+        assert!(!status.running);
+        assert_eq!(status.last_error.as_deref(), Some("нет связи"));
+    }
+}
@@ -0,0 +1,200 @@
+// src/disk_space.rs
+
+//!
+//! Проверка свободного места перед обновлением: новая версия раздачи может быть намного больше
+//! старой, и докачка не должна заполнить диск
+//!
+
+use crate::metainfo::MetainfoFile;
+use anyhow::{Context, Result};
+use qbit_rs::Qbit;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Настройки проверки места (секция `[free_space]` в config.toml)
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct FreeSpaceConfig {
+    /// Проверять свободное место перед добавлением новой версии
+    pub enabled: bool,
+    /// Сколько места должно остаться свободным после докачки, MB
+    pub reserve_mb: u64,
+}
+
+/// Обновление отложено: после докачки свободного места осталось бы меньше резерва
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "мало места на диске: нужно докачать {} MB, свободно {} MB, резерв {} MB",
+    .needed / MB, .free / MB, .reserve / MB
+)]
+pub struct LowSpace {
+    pub needed: u64,
+    pub free: u64,
+    pub reserve: u64,
+}
+
+const MB: u64 = 1024 * 1024;
+
+/// Файл торрента в qBittorrent: путь, размер и скачанная доля
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresentFile {
+    pub path: String,
+    pub size: u64,
+    pub progress: f64,
+}
+
+/// Сколько байт нужно докачать для новой версии: файлы с тем же путем и размером
+/// уже лежат на диске (в той доле, в которой скачаны)
+pub fn missing_bytes(present: &[PresentFile], new: &[MetainfoFile]) -> u64 {
+    let present: HashMap<&str, &PresentFile> =
+        present.iter().map(|f| (f.path.as_str(), f)).collect();
+
+    new.iter()
+        .map(|file| match present.get(file.path.as_str()) {
+            Some(old) if old.size == file.size => {
+                let have = (old.progress.clamp(0.0, 1.0) * old.size as f64) as u64;
+                file.size.saturating_sub(have)
+            }
+            _ => file.size,
+        })
+        .sum()
+}
+
+/// Лежат ли пути на одном диске. Для несуществующих путей берется ближайшая существующая
+/// папка; если выяснить не удалось, считается, что на одном.
+pub fn same_disk(a: &Path, b: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let device = |path: &Path| {
+            path.ancestors()
+                .find_map(|p| std::fs::metadata(p).ok())
+                .map(|m| m.dev())
+        };
+        match (device(a), device(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (a, b);
+        true
+    }
+}
+
+/// Свободное место, которое сообщает qBittorrent (на диске папки сохранения по умолчанию)
+pub async fn free_space(client: &Qbit) -> Result<u64> {
+    let data = client
+        .sync(0)
+        .await
+        .context("❌ Не удалось получить состояние qBittorrent")?;
+    data.server_state
+        .and_then(|state| state.get("free_space_on_disk").cloned())
+        .and_then(|value| u64::deserialize(value).ok())
+        .context("❌ qBittorrent не сообщил свободное место на диске")
+}
+
+/// Учет свободного места в течение запуска: место под уже добавленные обновления вычитается,
+/// потому что qBittorrent выделяет его не сразу
+#[derive(Debug, Default)]
+pub struct DiskBudget {
+    reserve: u64,
+    free: Mutex<Option<u64>>,
+}
+
+impl DiskBudget {
+    pub fn new(config: &FreeSpaceConfig) -> Self {
+        DiskBudget {
+            reserve: config.reserve_mb * MB,
+            free: Mutex::new(None),
+        }
+    }
+
+    /// Резервирует `needed` байт, если после этого останется не меньше резерва
+    pub async fn reserve(&self, client: &Qbit, needed: u64) -> Result<()> {
+        let cached = *self.free.lock().unwrap_or_else(|e| e.into_inner());
+        let free = match cached {
+            Some(free) => free,
+            None => free_space(client).await?,
+        };
+
+        let result = self.check(free, needed);
+        let remaining = if result.is_ok() { free - needed } else { free };
+        *self.free.lock().unwrap_or_else(|e| e.into_inner()) = Some(remaining);
+        result.map_err(Into::into)
+    }
+
+    fn check(&self, free: u64, needed: u64) -> Result<(), LowSpace> {
+        if free >= needed && free - needed >= self.reserve {
+            Ok(())
+        } else {
+            Err(LowSpace {
+                needed,
+                free,
+                reserve: self.reserve,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, size: u64) -> MetainfoFile {
+        MetainfoFile {
+            path: path.into(),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_missing_bytes() {
+        let present = vec![
+            PresentFile {
+                path: "Сериал/01.mkv".into(),
+                size: 1000,
+                progress: 1.0,
+            },
+            PresentFile {
+                path: "Сериал/02.mkv".into(),
+                size: 1000,
+                progress: 0.5,
+            },
+            PresentFile {
+                path: "Сериал/03.mkv".into(),
+                size: 1000,
+                progress: 1.0,
+            },
+        ];
+        let new = vec![
+            file("Сериал/01.mkv", 1000),
+            file("Сериал/02.mkv", 1000),
+            // Файл перезалит с другим размером — качается заново
+            file("Сериал/03.mkv", 1200),
+            file("Сериал/04.mkv", 1000),
+        ];
+        assert_eq!(missing_bytes(&present, &new), 500 + 1200 + 1000);
+    }
+
+    #[test]
+    fn test_check() {
+        let budget = DiskBudget::new(&FreeSpaceConfig {
+            enabled: true,
+            reserve_mb: 1,
+        });
+        assert!(budget.check(3 * MB, 2 * MB).is_ok());
+        assert_eq!(
+            budget.check(2 * MB, 2 * MB),
+            Err(LowSpace {
+                needed: 2 * MB,
+                free: 2 * MB,
+                reserve: MB
+            })
+        );
+        assert!(budget.check(MB, 2 * MB).is_err());
+    }
+}
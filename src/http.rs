@@ -0,0 +1,508 @@
+// src/http.rs
+
+//!
+//! Общий HTTP-клиент для запросов к трекерам: таймауты, повторы с экспоненциальной
+//! задержкой, обработка 429/`Retry-After` и ограничение частоты запросов к API
+//!
+
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, Proxy, Response, StatusCode};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/100.0.0.0 Safari/537.36";
+
+/// Бюджет значений в секунду до того, как станет известен лимит из `get_limit`
+const DEFAULT_RATE_BUDGET: u32 = 100;
+
+/// Настройки HTTP-клиента (секция `[http]` в config.toml)
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Общий таймаут запроса, секунды
+    pub timeout_secs: u64,
+    /// Таймаут установки соединения, секунды
+    pub connect_timeout_secs: u64,
+    pub user_agent: String,
+    /// Прокси по умолчанию для всех запросов к трекерам (`http://`, `https://`, `socks5://`).
+    /// К qBittorrent не применяется.
+    pub proxy: Option<ProxyConfig>,
+    /// Сколько раз повторять запрос при временных ошибках
+    pub max_retries: u32,
+    /// Начальная задержка перед повтором, миллисекунды (удваивается с каждой попыткой)
+    pub backoff_base_ms: u64,
+    /// Максимальная задержка перед повтором, секунды (ограничивает и `Retry-After`)
+    pub backoff_max_secs: u64,
+    /// Максимум простаивающих соединений на хост в пуле
+    pub pool_max_idle_per_host: usize,
+    /// Сколько запросов к API трекера выполнять одновременно
+    pub api_parallelism: usize,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            timeout_secs: 30,
+            connect_timeout_secs: 10,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            proxy: None,
+            max_retries: 4,
+            backoff_base_ms: 500,
+            backoff_max_secs: 30,
+            pool_max_idle_per_host: 8,
+            api_parallelism: 4,
+        }
+    }
+}
+
+/// Прокси: строка с адресом или таблица с адресом и учетными данными.
+///
+/// ```toml
+/// proxy = "socks5h://127.0.0.1:1080"
+/// proxy = { url = "http://proxy.local:3128", username = "user", password = "pass" }
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ProxyConfig {
+    Url(String),
+    WithAuth {
+        url: String,
+        username: String,
+        #[serde(default)]
+        password: String,
+    },
+}
+
+impl ProxyConfig {
+    pub fn url(&self) -> &str {
+        match self {
+            ProxyConfig::Url(url) | ProxyConfig::WithAuth { url, .. } => url,
+        }
+    }
+
+    fn to_proxy(&self) -> Result<Proxy> {
+        let proxy = Proxy::all(self.url())
+            .with_context(|| format!("❌ Некорректный адрес прокси '{}'", self.url()))?;
+        Ok(match self {
+            ProxyConfig::Url(_) => proxy,
+            ProxyConfig::WithAuth {
+                username, password, ..
+            } => proxy.basic_auth(username, password),
+        })
+    }
+}
+
+/// Ограничитель частоты в стиле "token bucket".
+///
+/// Бюджет измеряется в значениях (хешах/ID) в секунду: запрос с N значениями тратит N токенов.
+pub struct RateLimiter {
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    per_second: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.per_second);
+        self.last_refill = now;
+    }
+}
+
+impl RateLimiter {
+    pub fn new(per_second: u32) -> Self {
+        let per_second = per_second.max(1) as f64;
+        RateLimiter {
+            bucket: Mutex::new(Bucket {
+                per_second,
+                tokens: per_second,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Меняет бюджет (например, после получения лимита от API)
+    pub async fn set_budget(&self, per_second: u32) {
+        let mut bucket = self.bucket.lock().await;
+        bucket.refill();
+        bucket.per_second = per_second.max(1) as f64;
+        bucket.tokens = bucket.tokens.min(bucket.per_second);
+    }
+
+    /// Ждет, пока в бюджете наберется `cost` токенов, и списывает их
+    pub async fn acquire(&self, cost: u32) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().await;
+                bucket.refill();
+                // Запрос дороже всего бюджета все равно должен когда-нибудь пройти
+                let cost = (cost as f64).min(bucket.per_second);
+                if bucket.tokens >= cost {
+                    bucket.tokens -= cost;
+                    return;
+                }
+                Duration::from_secs_f64((cost - bucket.tokens) / bucket.per_second)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Границы корзин гистограммы длительности запросов, секунды
+pub const LATENCY_BUCKETS: [f64; 7] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Счетчики запросов к трекерам для метрик. Каждая попытка (включая повторы) считается отдельно.
+#[derive(Default)]
+pub struct HttpStats {
+    requests: AtomicU64,
+    errors: AtomicU64,
+    /// Число запросов по корзинам `LATENCY_BUCKETS` (последняя — все, что дольше)
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    latency_sum_micros: AtomicU64,
+}
+
+impl HttpStats {
+    /// Учитывает одну попытку запроса
+    pub fn record(&self, elapsed: Duration, failed: bool) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        let seconds = elapsed.as_secs_f64();
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|&upper| seconds <= upper)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.latency_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.latency_sum_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn requests(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    /// Попытки, закончившиеся сетевой ошибкой или статусом 4xx/5xx
+    pub fn errors(&self) -> u64 {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    /// Накопленное число запросов не дольше каждой из границ `LATENCY_BUCKETS`
+    pub fn latency_cumulative(&self) -> Vec<u64> {
+        let mut total = 0;
+        self.latency_buckets[..LATENCY_BUCKETS.len()]
+            .iter()
+            .map(|bucket| {
+                total += bucket.load(Ordering::Relaxed);
+                total
+            })
+            .collect()
+    }
+
+    pub fn latency_sum_secs(&self) -> f64 {
+        self.latency_sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0
+    }
+}
+
+/// HTTP-клиент, общий для всех запросов к трекерам. Дешево клонируется.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: Client,
+    config: Arc<HttpConfig>,
+    limiter: Arc<RateLimiter>,
+    stats: Arc<HttpStats>,
+}
+
+impl HttpClient {
+    pub fn new(config: &HttpConfig) -> Result<Self> {
+        Ok(HttpClient {
+            client: build_client(config, config.proxy.as_ref())?,
+            config: Arc::new(config.clone()),
+            limiter: Arc::new(RateLimiter::new(DEFAULT_RATE_BUDGET)),
+            stats: Arc::new(HttpStats::default()),
+        })
+    }
+
+    /// Клиент с другим прокси. Ограничитель частоты и статистика остаются общими.
+    pub fn with_proxy(&self, proxy: &ProxyConfig) -> Result<Self> {
+        Ok(HttpClient {
+            client: build_client(&self.config, Some(proxy))?,
+            ..self.clone()
+        })
+    }
+
+    /// Ограничитель частоты запросов к API
+    pub fn limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+
+    /// Статистика запросов (общая для всех клонов клиента)
+    pub fn stats(&self) -> Arc<HttpStats> {
+        self.stats.clone()
+    }
+
+    /// Сколько запросов к API можно выполнять одновременно
+    pub fn parallelism(&self) -> usize {
+        self.config.api_parallelism.max(1)
+    }
+
+    /// GET-запрос с повторами при временных ошибках (без учета бюджета API)
+    pub async fn get(
+        &self,
+        url: &str,
+        headers: Option<&HeaderMap>,
+    ) -> Result<Response, reqwest::Error> {
+        self.send(url, headers, 0).await
+    }
+
+    /// GET-запрос, тратящий `cost` значений из общего бюджета ограничителя
+    pub async fn get_budgeted(
+        &self,
+        url: &str,
+        headers: Option<&HeaderMap>,
+        cost: u32,
+    ) -> Result<Response, reqwest::Error> {
+        self.send(url, headers, cost).await
+    }
+
+    /// Выполняет запрос, повторяя его при сетевых ошибках, 429 и 5xx.
+    ///
+    /// Если попытки закончились на "временном" статусе, возвращается последний ответ,
+    /// чтобы вызывающий код сам решил, что с ним делать.
+    async fn send(
+        &self,
+        url: &str,
+        headers: Option<&HeaderMap>,
+        cost: u32,
+    ) -> Result<Response, reqwest::Error> {
+        let mut attempt = 0;
+        loop {
+            if cost > 0 {
+                self.limiter.acquire(cost).await;
+            }
+
+            let mut request = self.client.get(url);
+            if let Some(headers) = headers {
+                request = request.headers(headers.clone());
+            }
+
+            let started = Instant::now();
+            let result = request.send().await;
+            let failed = match &result {
+                Ok(response) => !response.status().is_success(),
+                Err(_) => true,
+            };
+            self.stats.record(started.elapsed(), failed);
+            let retries_left = attempt < self.config.max_retries;
+
+            let delay = match &result {
+                Ok(response) if is_transient_status(response.status()) && retries_left => {
+                    let delay = retry_after(response)
+                        .map(|requested| self.limit_retry_after(requested))
+                        .unwrap_or_else(|| self.backoff(attempt));
+                    log::warn!(
+                        "⚠️ {} вернул {}, повтор через {:.1} с",
+                        url_for_log(url),
+                        response.status(),
+                        delay.as_secs_f64()
+                    );
+                    delay
+                }
+                Err(e) if is_transient_error(e) && retries_left => {
+                    let delay = self.backoff(attempt);
+                    log::warn!(
+                        "⚠️ Ошибка запроса к {}: {}. Повтор через {:.1} с",
+                        url_for_log(url),
+                        e,
+                        delay.as_secs_f64()
+                    );
+                    delay
+                }
+                _ => return result,
+            };
+
+            attempt += 1;
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .config
+            .backoff_base_ms
+            .saturating_mul(1u64 << attempt.min(16));
+        Duration::from_millis(delay).min(Duration::from_secs(self.config.backoff_max_secs))
+    }
+
+    /// Задержка из `Retry-After`, но не больше `backoff_max_secs`: ошибочный заголовок
+    /// не должен останавливать запуск на часы
+    fn limit_retry_after(&self, requested: Duration) -> Duration {
+        let max = Duration::from_secs(self.config.backoff_max_secs);
+        if requested > max {
+            log::warn!(
+                "⚠️ Сервер просит подождать {} с, ждем не больше {} с (http.backoff_max_secs)",
+                requested.as_secs(),
+                max.as_secs()
+            );
+            return max;
+        }
+        requested
+    }
+}
+
+fn build_client(config: &HttpConfig, proxy: Option<&ProxyConfig>) -> Result<Client> {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+        .user_agent(config.user_agent.clone())
+        .pool_max_idle_per_host(config.pool_max_idle_per_host);
+
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy.to_proxy()?);
+    }
+
+    builder.build().context("❌ Не удалось создать HTTP-клиент")
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+fn is_transient_error(e: &reqwest::Error) -> bool {
+    e.is_timeout() || e.is_connect() || e.is_request()
+}
+
+/// Задержка из заголовка `Retry-After` (поддерживается только формат в секундах)
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Адрес без параметров запроса: в них могут быть длинные списки хешей
+fn url_for_log(url: &str) -> &str {
+    url.split_once('?').map(|(base, _)| base).unwrap_or(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_waits_for_budget() {
+        let limiter = RateLimiter::new(10);
+        let start = Instant::now();
+
+        limiter.acquire(10).await;
+        assert!(start.elapsed() < Duration::from_millis(1));
+
+        // Бюджет исчерпан: следующие 5 значений будут доступны через полсекунды
+        limiter.acquire(5).await;
+        assert!(start.elapsed() >= Duration::from_millis(500));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_shared_between_tasks() {
+        let limiter = Arc::new(RateLimiter::new(10));
+        let start = Instant::now();
+
+        // 4 параллельных запроса по 10 значений при бюджете 10/с: последний ждет ~3 с
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let limiter = limiter.clone();
+                tokio::spawn(async move { limiter.acquire(10).await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert!(start.elapsed() >= Duration::from_secs(3));
+        assert!(start.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_stats_latency_buckets() {
+        let stats = HttpStats::default();
+        stats.record(Duration::from_millis(50), false);
+        stats.record(Duration::from_millis(700), true);
+        stats.record(Duration::from_secs(60), false);
+
+        assert_eq!(stats.requests(), 3);
+        assert_eq!(stats.errors(), 1);
+        assert_eq!(stats.latency_cumulative(), vec![1, 1, 1, 2, 2, 2, 2]);
+        assert!((stats.latency_sum_secs() - 60.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_proxy_config_formats() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            proxy: ProxyConfig,
+        }
+
+        let plain: Wrapper =
+            serde_json::from_str(r#"{"proxy": "socks5://127.0.0.1:1080"}"#).unwrap();
+        assert_eq!(
+            plain.proxy,
+            ProxyConfig::Url("socks5://127.0.0.1:1080".into())
+        );
+
+        let with_auth: Wrapper = serde_json::from_str(
+            r#"{"proxy": {"url": "http://proxy:3128", "username": "u", "password": "p"}}"#,
+        )
+        .unwrap();
+        assert_eq!(with_auth.proxy.url(), "http://proxy:3128");
+        assert!(with_auth.proxy.to_proxy().is_ok());
+
+        assert!(ProxyConfig::Url("не адрес".into()).to_proxy().is_err());
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let http = HttpClient::new(&HttpConfig {
+            backoff_base_ms: 1000,
+            backoff_max_secs: 5,
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(http.backoff(0), Duration::from_secs(1));
+        assert_eq!(http.backoff(2), Duration::from_secs(4));
+        assert_eq!(http.backoff(10), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_retry_after_is_capped() {
+        let http = HttpClient::new(&HttpConfig {
+            backoff_max_secs: 30,
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(
+            http.limit_retry_after(Duration::from_secs(10)),
+            Duration::from_secs(10)
+        );
+        assert_eq!(
+            http.limit_retry_after(Duration::from_secs(6 * 60 * 60)),
+            Duration::from_secs(30)
+        );
+    }
+}
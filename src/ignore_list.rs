@@ -0,0 +1,94 @@
+// src/ignore_list.rs
+
+//!
+//! Торренты, которые пользователь навсегда исключил из обработки (в интерактивном режиме)
+//!
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct IgnoredTorrent {
+    pub name: String,
+    /// Действие, от которого отказались
+    pub action: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct IgnoreList {
+    /// Хеш торрента → сведения для человека, читающего файл
+    torrents: BTreeMap<String, IgnoredTorrent>,
+}
+
+impl IgnoreList {
+    /// Загружает список. Отсутствующий файл дает пустой список.
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read(path) {
+            Ok(data) => serde_json::from_slice(&data)
+                .with_context(|| format!("❌ Некорректный список игнорируемых {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("❌ Не удалось прочитать {}", path.display())),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("❌ Не удалось создать папку {}", dir.display()))?;
+        }
+        let data = serde_json::to_vec_pretty(self)?;
+        std::fs::write(path, data).with_context(|| {
+            format!(
+                "❌ Не удалось сохранить список игнорируемых {}",
+                path.display()
+            )
+        })
+    }
+
+    pub fn contains(&self, hash: &str) -> bool {
+        self.torrents.contains_key(hash)
+    }
+
+    pub fn insert(&mut self, hash: String, torrent: IgnoredTorrent) {
+        self.torrents.insert(hash, torrent);
+    }
+
+    pub fn len(&self) -> usize {
+        self.torrents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.torrents.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignore_list_roundtrip() {
+        let path = Path::new("ignored.temp_roundtrip.json");
+        let _ = std::fs::remove_file(path);
+
+        let mut list = IgnoreList::load(path).unwrap();
+        assert!(list.is_empty());
+
+        list.insert(
+            "aa".into(),
+            IgnoredTorrent {
+                name: "test".into(),
+                action: "удалить".into(),
+            },
+        );
+        list.save(path).unwrap();
+
+        let loaded = IgnoreList::load(path).unwrap();
+        assert!(loaded.contains("aa"));
+        assert!(!loaded.contains("bb"));
+
+        let _ = std::fs::remove_file(path);
+    }
+}
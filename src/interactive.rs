@@ -0,0 +1,154 @@
+// src/interactive.rs
+
+//!
+//! Интерактивное подтверждение действий в терминале: перед отправкой в qBittorrent
+//! каждое изменение можно принять, пропустить или навсегда игнорировать
+//!
+
+use crate::ignore_list::{IgnoreList, IgnoredTorrent};
+use crate::metainfo::{diff_files, parse_files, MetainfoFile};
+use crate::plan::PlanEntry;
+use crate::rules::RuleAction;
+use crate::shutdown::TempFile;
+use crate::topic_id::TopicId;
+use crate::torrent::Torrent;
+use crate::trackers::TrackerProvider;
+use anyhow::Result;
+use dialoguer::Select;
+use qbit_rs::Qbit;
+use std::collections::HashMap;
+
+/// Решение пользователя по одному действию
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    Accept,
+    Skip,
+    IgnoreForever,
+}
+
+/// Итог просмотра действий
+#[derive(Default)]
+pub struct Reviewed {
+    /// Принятые действия
+    pub entries: Vec<PlanEntry>,
+    /// Хеш торрента → .torrent новой версии, скачанный для сравнения файлов.
+    /// Обновление использует его, а не скачивает заново.
+    pub metainfo: HashMap<String, TempFile>,
+}
+
+/// Показывает действия по одному и возвращает только принятые.
+///
+/// Уведомления подтверждения не требуют. Навсегда игнорируемые торренты добавляются в `ignore`.
+pub async fn review(
+    client: &Qbit,
+    provider: &dyn TrackerProvider,
+    torrents: &HashMap<&str, &Torrent>,
+    entries: Vec<PlanEntry>,
+    ignore: &mut IgnoreList,
+) -> Result<Reviewed> {
+    let total = entries.len();
+    let mut reviewed = Reviewed::default();
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        let Some(torrent) = torrents.get(entry.hash.as_str()) else {
+            continue;
+        };
+        if entry.action == RuleAction::Notify {
+            reviewed.entries.push(entry);
+            continue;
+        }
+
+        println!();
+        println!("━━━ [{}/{}] {} ━━━", index + 1, total, provider.name());
+        println!("{}", torrent);
+        println!(
+            "Действие: {} (правило '{}'; {})",
+            entry.action, entry.rule, entry.reason
+        );
+        for change in &entry.changes {
+            println!("  • {}", change);
+        }
+
+        let mut metainfo = None;
+        if let (RuleAction::Update, Some(topic_id)) = (&entry.action, entry.topic_id) {
+            match file_diff(client, provider, &entry.hash, topic_id).await {
+                Ok((lines, file)) => {
+                    if lines.is_empty() {
+                        println!("Состав файлов не изменился.");
+                    } else {
+                        println!("Изменения в файлах:");
+                        for line in lines {
+                            println!("  {}", line);
+                        }
+                    }
+                    metainfo = Some(file);
+                }
+                Err(e) => println!("Не удалось сравнить файлы: {:#}", e),
+            }
+        }
+
+        match prompt(format!("{} '{}'?", entry.action, torrent.name)).await? {
+            Choice::Accept => {
+                if let Some(file) = metainfo {
+                    reviewed.metainfo.insert(entry.hash.clone(), file);
+                }
+                reviewed.entries.push(entry);
+            }
+            Choice::Skip => log::info!("Пропущено пользователем: '{}'", torrent.name),
+            Choice::IgnoreForever => {
+                log::info!("Навсегда игнорируется: '{}'", torrent.name);
+                ignore.insert(
+                    entry.hash.clone(),
+                    IgnoredTorrent {
+                        name: torrent.name.clone(),
+                        action: entry.action.to_string(),
+                    },
+                );
+            }
+        }
+    }
+
+    Ok(reviewed)
+}
+
+async fn prompt(question: String) -> Result<Choice> {
+    // dialoguer блокирует поток, поэтому уводим его с асинхронного исполнителя
+    let selection = tokio::task::spawn_blocking(move || {
+        Select::new()
+            .with_prompt(question)
+            .items(["Принять", "Пропустить", "Игнорировать всегда"])
+            .default(0)
+            .interact()
+    })
+    .await??;
+
+    Ok(match selection {
+        0 => Choice::Accept,
+        1 => Choice::Skip,
+        _ => Choice::IgnoreForever,
+    })
+}
+
+/// Сравнивает файлы торрента в qBittorrent с файлами новой версии раздачи.
+/// Возвращает строки различий и скачанный .torrent новой версии.
+async fn file_diff(
+    client: &Qbit,
+    provider: &dyn TrackerProvider,
+    hash: &str,
+    topic_id: TopicId,
+) -> Result<(Vec<String>, TempFile)> {
+    let old: Vec<MetainfoFile> = client
+        .get_torrent_contents(hash, None)
+        .await?
+        .into_iter()
+        .map(|c| MetainfoFile {
+            path: c.name,
+            size: c.size,
+        })
+        .collect();
+
+    let file = TempFile::new(provider.download_metainfo(topic_id).await?);
+    let new = parse_files(&tokio::fs::read(file.path()).await?)?;
+
+    Ok((diff_files(&old, &new), file))
+}
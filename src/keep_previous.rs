@@ -0,0 +1,458 @@
+// src/keep_previous.rs
+
+//!
+//! Сохранение предыдущей версии данных: перед добавлением новой версии файлы, которые она
+//! перезапишет, переносятся или копируются в папку версий (на случай, если обновление хуже)
+//!
+
+use crate::metainfo::{Layout, MetainfoFile};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Как сохранять файлы.
+///
+/// Жестких ссылок нет: qBittorrent перезаписывает файлы на месте, и ссылка изменилась бы вместе
+/// с оригиналом.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PreserveMode {
+    /// Копировать: совпадающие части файла новая версия возьмет с диска
+    #[default]
+    Copy,
+    /// Переносить: быстро и без лишнего места, но файл скачивается заново целиком
+    Move,
+}
+
+/// Настройки сохранения предыдущей версии (секция `[keep_previous]` в config.toml)
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct KeepPreviousConfig {
+    pub enabled: bool,
+    pub mode: PreserveMode,
+    /// Папка версий: `<dir>/<ID темы>/<время>/`
+    pub dir: String,
+    /// Сколько версий хранить для одной раздачи (0 — не ограничивать)
+    pub keep_versions: usize,
+    /// Удалять версии старше этого числа дней
+    pub max_age_days: Option<u64>,
+    /// Начало путей в qBittorrent, которое заменяется на `local_prefix`
+    /// (если qBittorrent работает на другой машине или в контейнере)
+    pub remote_prefix: Option<String>,
+    pub local_prefix: Option<String>,
+}
+
+impl Default for KeepPreviousConfig {
+    fn default() -> Self {
+        KeepPreviousConfig {
+            enabled: false,
+            mode: PreserveMode::Copy,
+            dir: "cache/versions".to_string(),
+            keep_versions: 3,
+            max_age_days: None,
+            remote_prefix: None,
+            local_prefix: None,
+        }
+    }
+}
+
+impl KeepPreviousConfig {
+    /// Путь к файлу торрента на этой машине
+    pub fn local_path(&self, save_path: &str, name: &str) -> PathBuf {
+        let save_path = match (&self.remote_prefix, &self.local_prefix) {
+            (Some(remote), Some(local)) => match save_path.strip_prefix(remote.as_str()) {
+                Some(rest) => format!("{}{}", local, rest),
+                None => save_path.to_string(),
+            },
+            _ => save_path.to_string(),
+        };
+        Path::new(&save_path).join(name)
+    }
+}
+
+/// Описание сохраненной версии (`version.json` в ее папке)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PreservedVersion {
+    pub name: String,
+    pub hash: String,
+    pub new_hash: String,
+    /// Папка сохранения в qBittorrent, куда возвращать файлы
+    pub save_path: String,
+    pub files: Vec<String>,
+}
+
+const VERSION_FILE: &str = "version.json";
+
+/// Файлы на диске (`on_disk`), которые перезапишет новая версия: с тем же путем в новой
+/// версии и другим содержимым.
+///
+/// Содержимое сравнивается по хешам частей старой (`old`) и новой раздачи. Совпадение размера
+/// ничего не гарантирует, поэтому если сравнить нельзя (нет хешей, другой размер части или
+/// другое положение файла относительно частей), файл считается измененным.
+pub fn overwritten_files<'a>(
+    on_disk: &'a [MetainfoFile],
+    old: Option<&Layout>,
+    new: &Layout,
+) -> Vec<&'a MetainfoFile> {
+    let new_offsets: HashMap<&str, (&MetainfoFile, u64)> = new
+        .offsets()
+        .map(|(f, offset)| (f.path.as_str(), (f, offset)))
+        .collect();
+    let old = old
+        .filter(|old| old.has_pieces() && new.has_pieces() && old.piece_length == new.piece_length);
+    let old_offsets: HashMap<&str, (&MetainfoFile, u64)> = old
+        .iter()
+        .flat_map(|old| old.offsets())
+        .map(|(f, offset)| (f.path.as_str(), (f, offset)))
+        .collect();
+
+    on_disk
+        .iter()
+        .filter(|file| {
+            let Some(&(new_file, new_offset)) = new_offsets.get(file.path.as_str()) else {
+                return false;
+            };
+            let unchanged = match (old, old_offsets.get(file.path.as_str())) {
+                (Some(old), Some(&(old_file, old_offset))) => {
+                    old_file.size == new_file.size
+                        && same_pieces(old, old_offset, new, new_offset, new_file.size)
+                }
+                _ => false,
+            };
+            !unchanged
+        })
+        .collect()
+}
+
+/// Совпадают ли хеши всех частей, которые занимает файл размера `size` в обеих раздачах
+fn same_pieces(old: &Layout, old_offset: u64, new: &Layout, new_offset: u64, size: u64) -> bool {
+    if size == 0 {
+        return true;
+    }
+    let piece = new.piece_length;
+    // Файл сдвинут относительно границ частей — хеши не сравнить
+    if old_offset % piece != new_offset % piece {
+        return false;
+    }
+    let (old_first, new_first) = (old_offset / piece, new_offset / piece);
+    let count = (new_offset + size - 1) / piece - new_first + 1;
+    (0..count).all(|i| {
+        matches!(
+            (old.piece(old_first + i), new.piece(new_first + i)),
+            (Some(a), Some(b)) if a == b
+        )
+    })
+}
+
+/// Сохраненная версия данных
+#[derive(Debug)]
+pub struct Preserved {
+    pub dir: PathBuf,
+    /// Сохраненные файлы `(путь на диске, путь в торренте)`
+    files: Vec<(PathBuf, String)>,
+    mode: PreserveMode,
+}
+
+impl Preserved {
+    /// Отменяет сохранение, если обновление не состоялось: перенесенные файлы возвращаются
+    /// на место, папка версии удаляется
+    pub fn undo(self) -> Result<()> {
+        if self.mode == PreserveMode::Move {
+            for (source, name) in &self.files {
+                let saved = self.dir.join(name);
+                transfer(&saved, source, PreserveMode::Move).with_context(|| {
+                    format!(
+                        "❌ Не удалось вернуть {} в {}",
+                        saved.display(),
+                        source.display()
+                    )
+                })?;
+            }
+        }
+        let _ = std::fs::remove_dir_all(&self.dir);
+        Ok(())
+    }
+}
+
+fn transfer(from: &Path, to: &Path, mode: PreserveMode) -> std::io::Result<()> {
+    match mode {
+        PreserveMode::Copy => std::fs::copy(from, to).map(|_| ()),
+        // На другой диск перенести нельзя — копируем и удаляем
+        PreserveMode::Move => std::fs::rename(from, to).or_else(|_| {
+            std::fs::copy(from, to)?;
+            std::fs::remove_file(from)
+        }),
+    }
+}
+
+/// Сохраняет файлы `(путь на диске, путь в торренте)` в новую папку версии внутри `topic_dir`.
+/// При ошибке уже перенесенные файлы возвращаются на место.
+pub fn preserve(
+    topic_dir: &Path,
+    files: &[(PathBuf, String)],
+    version: &PreservedVersion,
+    mode: PreserveMode,
+) -> Result<Preserved> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut preserved = Preserved {
+        dir: topic_dir.join(now.to_string()),
+        files: Vec::new(),
+        mode,
+    };
+
+    match save_files(&mut preserved, files, version) {
+        Ok(()) => Ok(preserved),
+        Err(e) => {
+            if let Err(undo) = preserved.undo() {
+                log::error!("{:#}", undo);
+            }
+            Err(e)
+        }
+    }
+}
+
+fn save_files(
+    preserved: &mut Preserved,
+    files: &[(PathBuf, String)],
+    version: &PreservedVersion,
+) -> Result<()> {
+    let version_dir = preserved.dir.clone();
+    for (source, name) in files {
+        let target = version_dir.join(name);
+        if let Some(dir) = target.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("❌ Не удалось создать папку {}", dir.display()))?;
+        }
+        transfer(source, &target, preserved.mode).with_context(|| {
+            format!(
+                "❌ Не удалось сохранить {} в {}",
+                source.display(),
+                target.display()
+            )
+        })?;
+        preserved.files.push((source.clone(), name.clone()));
+    }
+
+    std::fs::create_dir_all(&version_dir)
+        .with_context(|| format!("❌ Не удалось создать папку {}", version_dir.display()))?;
+    std::fs::write(
+        version_dir.join(VERSION_FILE),
+        serde_json::to_vec_pretty(version)?,
+    )
+    .with_context(|| {
+        format!(
+            "❌ Не удалось сохранить описание версии в {}",
+            version_dir.display()
+        )
+    })
+}
+
+/// Удаляет лишние версии: в каждой раздаче остается не больше `keep` последних (0 — сколько угодно),
+/// и все старше `max_age`. Возвращает число удаленных версий.
+pub fn prune(dir: &Path, keep: usize, max_age: Option<Duration>) -> usize {
+    let Ok(topics) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut removed = 0;
+
+    for topic in topics.flatten().filter(|e| e.path().is_dir()) {
+        let Ok(entries) = std::fs::read_dir(topic.path()) else {
+            continue;
+        };
+        // Папки версий названы временем создания
+        let mut versions: Vec<(u64, PathBuf)> = entries
+            .flatten()
+            .filter_map(|e| Some((e.file_name().to_str()?.parse().ok()?, e.path())))
+            .collect();
+        versions.sort_by_key(|(created, _)| std::cmp::Reverse(*created));
+
+        for (index, (created, path)) in versions.iter().enumerate() {
+            let expired = max_age.is_some_and(|age| now.saturating_sub(*created) >= age.as_secs());
+            if ((keep > 0 && index >= keep) || expired) && std::fs::remove_dir_all(path).is_ok() {
+                log::info!("🗑️ Удалена сохраненная версия {}", path.display());
+                removed += 1;
+            }
+        }
+        // Пустая папка раздачи больше не нужна
+        let _ = std::fs::remove_dir(topic.path());
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, size: u64) -> MetainfoFile {
+        MetainfoFile {
+            path: path.into(),
+            size,
+        }
+    }
+
+    /// Раздача `a` с частями по 4 байта; `pieces` — буква хеша каждой части
+    fn layout(files: &[(&str, u64)], pieces: &str) -> Layout {
+        let mut data = String::from("d4:infod5:filesl");
+        for (name, size) in files {
+            data += &format!("d6:lengthi{}e4:pathl{}:{}ee", size, name.len(), name);
+        }
+        data += &format!("e4:name1:a12:piece lengthi4e6:pieces{}:", pieces.len() * 20);
+        for c in pieces.chars() {
+            data += &c.to_string().repeat(20);
+        }
+        data += "ee";
+        crate::metainfo::parse_layout(data.as_bytes()).unwrap()
+    }
+
+    fn paths<'a>(files: &[&'a MetainfoFile]) -> Vec<&'a str> {
+        files.iter().map(|f| f.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_overwritten_files() {
+        let on_disk = vec![file("a/1.mkv", 4), file("a/2.mkv", 4), file("a/3.mkv", 4)];
+        let old = layout(&[("1.mkv", 4), ("2.mkv", 4), ("3.mkv", 4)], "ABC");
+        // 1.mkv не изменился, у 2.mkv тот же размер, но другое содержимое, 3.mkv удален
+        let new = layout(&[("1.mkv", 4), ("2.mkv", 4), ("4.mkv", 8)], "AXDD");
+        assert_eq!(
+            paths(&overwritten_files(&on_disk, Some(&old), &new)),
+            ["a/2.mkv"]
+        );
+
+        // Изменился размер
+        let new = layout(&[("1.mkv", 4), ("2.mkv", 6)], "ABB");
+        assert_eq!(
+            paths(&overwritten_files(&on_disk, Some(&old), &new)),
+            ["a/2.mkv"]
+        );
+
+        // Файл сдвинут относительно частей: сравнить нельзя
+        let new = layout(&[("0.srt", 2), ("1.mkv", 4), ("2.mkv", 4)], "ABC");
+        assert_eq!(
+            paths(&overwritten_files(&on_disk, Some(&old), &new)),
+            ["a/1.mkv", "a/2.mkv"]
+        );
+    }
+
+    #[test]
+    fn test_overwritten_files_without_pieces() {
+        let on_disk = vec![file("a/1.mkv", 4), file("a/2.mkv", 4)];
+        let new = layout(&[("1.mkv", 4), ("2.mkv", 4)], "AB");
+
+        // Старую раздачу получить не удалось — все файлы с тем же путем
+        assert_eq!(
+            paths(&overwritten_files(&on_disk, None, &new)),
+            ["a/1.mkv", "a/2.mkv"]
+        );
+
+        // Другой размер части
+        let mut old = layout(&[("1.mkv", 4), ("2.mkv", 4)], "AB");
+        old.piece_length = 2;
+        assert_eq!(
+            paths(&overwritten_files(&on_disk, Some(&old), &new)),
+            ["a/1.mkv", "a/2.mkv"]
+        );
+    }
+
+    #[test]
+    fn test_local_path() {
+        let mut config = KeepPreviousConfig::default();
+        assert_eq!(
+            config.local_path("/downloads", "a/1.mkv"),
+            Path::new("/downloads/a/1.mkv")
+        );
+        config.remote_prefix = Some("/downloads".into());
+        config.local_prefix = Some("/mnt/nas".into());
+        assert_eq!(
+            config.local_path("/downloads/video", "1.mkv"),
+            Path::new("/mnt/nas/video/1.mkv")
+        );
+        assert_eq!(
+            config.local_path("/other", "1.mkv"),
+            Path::new("/other/1.mkv")
+        );
+    }
+
+    #[test]
+    fn test_preserve_and_prune() {
+        let dir = std::env::temp_dir().join("tracker-updater-keep-previous-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let data = dir.join("data");
+        std::fs::create_dir_all(data.join("a")).unwrap();
+        std::fs::write(data.join("a/1.mkv"), b"old").unwrap();
+
+        let version = PreservedVersion {
+            name: "a".into(),
+            hash: "old".into(),
+            new_hash: "new".into(),
+            save_path: data.display().to_string(),
+            files: vec!["a/1.mkv".into()],
+        };
+        let files = [(data.join("a/1.mkv"), "a/1.mkv".to_string())];
+        let topic_dir = dir.join("versions/101");
+        let saved = preserve(&topic_dir, &files, &version, PreserveMode::Move)
+            .unwrap()
+            .dir;
+        assert_eq!(std::fs::read(saved.join("a/1.mkv")).unwrap(), b"old");
+        assert!(saved.join(VERSION_FILE).exists());
+        assert!(!data.join("a/1.mkv").exists());
+
+        // Более старые версии сверх лимита удаляются
+        std::fs::create_dir_all(topic_dir.join("1")).unwrap();
+        std::fs::create_dir_all(topic_dir.join("2")).unwrap();
+        assert_eq!(prune(&dir.join("versions"), 2, None), 1);
+        assert!(!topic_dir.join("1").exists());
+        assert!(topic_dir.join("2").exists() && saved.exists());
+
+        // И все, что старше срока хранения
+        assert_eq!(
+            prune(&dir.join("versions"), 10, Some(Duration::from_secs(3600))),
+            1
+        );
+        assert!(saved.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_undo_returns_moved_files() {
+        let dir = std::env::temp_dir().join("tracker-updater-keep-previous-undo-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let data = dir.join("data");
+        std::fs::create_dir_all(&data).unwrap();
+        std::fs::write(data.join("1.mkv"), b"old").unwrap();
+
+        let version = PreservedVersion {
+            name: "1.mkv".into(),
+            hash: "old".into(),
+            new_hash: "new".into(),
+            save_path: data.display().to_string(),
+            files: vec!["1.mkv".into()],
+        };
+        let files = [(data.join("1.mkv"), "1.mkv".to_string())];
+        let preserved = preserve(
+            &dir.join("versions/101"),
+            &files,
+            &version,
+            PreserveMode::Move,
+        )
+        .unwrap();
+        let version_dir = preserved.dir.clone();
+        assert!(!data.join("1.mkv").exists());
+
+        preserved.undo().unwrap();
+        assert_eq!(std::fs::read(data.join("1.mkv")).unwrap(), b"old");
+        assert!(!version_dir.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,192 @@
+// src/keeper.rs
+
+//!
+//! Режим хранителя: теги `rt-seeds:0`, `rt-seeds:1-3`, `rt-seeds:4+` по числу сидов на трекере,
+//! чтобы в самом клиенте было видно, каким раздачам мы нужнее всего
+//!
+
+use crate::torrent::Torrent;
+use anyhow::Result;
+use qbit_rs::Qbit;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Настройки режима хранителя (секция `[keeper]` в config.toml)
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct KeeperConfig {
+    pub enabled: bool,
+    /// Префикс тегов; все теги с этим префиксом считаются служебными
+    pub tag_prefix: String,
+    /// Нижние границы групп по возрастанию: `[1, 4]` дает группы `0`, `1-3`, `4+`
+    pub thresholds: Vec<u32>,
+}
+
+impl Default for KeeperConfig {
+    fn default() -> Self {
+        KeeperConfig {
+            enabled: false,
+            tag_prefix: "rt-seeds:".to_string(),
+            thresholds: vec![1, 4],
+        }
+    }
+}
+
+impl KeeperConfig {
+    /// Тег для раздачи с указанным числом сидов
+    pub fn seed_tag(&self, seeders: u32) -> String {
+        format!("{}{}", self.tag_prefix, self.seed_group(seeders))
+    }
+
+    /// Группа по числу сидов без префикса: `0`, `1-3`, `4+`
+    pub fn seed_group(&self, seeders: u32) -> String {
+        let mut lower = 0;
+        for &upper in self.thresholds.iter().filter(|&&t| t > 0) {
+            if upper <= lower {
+                continue;
+            }
+            if seeders < upper {
+                return range_label(lower, upper - 1);
+            }
+            lower = upper;
+        }
+        format!("{}+", lower)
+    }
+}
+
+fn range_label(from: u32, to: u32) -> String {
+    if from == to {
+        from.to_string()
+    } else {
+        format!("{}-{}", from, to)
+    }
+}
+
+/// Изменения тегов одного торрента: какой тег добавить и какие устаревшие убрать
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagChange {
+    pub add: Option<String>,
+    pub remove: Vec<String>,
+}
+
+/// Сравнивает текущие теги торрента (строка qBittorrent через запятую) с нужным тегом
+pub fn tag_change(current_tags: &str, desired: &str, prefix: &str) -> TagChange {
+    let mut has_desired = false;
+    let mut remove = Vec::new();
+
+    for tag in current_tags.split(',').map(str::trim) {
+        if tag == desired {
+            has_desired = true;
+        } else if tag.starts_with(prefix) {
+            remove.push(tag.to_string());
+        }
+    }
+
+    TagChange {
+        add: (!has_desired).then(|| desired.to_string()),
+        remove,
+    }
+}
+
+/// Расставляет теги по числу сидов. Возвращает число торрентов, у которых изменились теги.
+pub async fn apply_seed_tags(
+    client: &Qbit,
+    config: &KeeperConfig,
+    torrents: &[&Torrent],
+    dry_run: bool,
+) -> Result<usize> {
+    // Группируем по тегу, чтобы выполнить по одному запросу на тег
+    let mut to_add: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut to_remove: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut changed = 0;
+
+    for torrent in torrents {
+        let desired = config.seed_tag(torrent.seeders);
+        let change = tag_change(&torrent.tags, &desired, &config.tag_prefix);
+        if change.add.is_none() && change.remove.is_empty() {
+            continue;
+        }
+        changed += 1;
+
+        log::debug!(
+            "Тег хранителя для '{}' (сидов: {}): {}",
+            torrent.name,
+            torrent.seeders,
+            desired
+        );
+        if let Some(tag) = change.add {
+            to_add
+                .entry(tag)
+                .or_default()
+                .push(torrent.torrent_hash.clone());
+        }
+        for tag in change.remove {
+            to_remove
+                .entry(tag)
+                .or_default()
+                .push(torrent.torrent_hash.clone());
+        }
+    }
+
+    if dry_run || changed == 0 {
+        return Ok(changed);
+    }
+
+    for (tag, hashes) in to_remove {
+        client.remove_torrent_tags(hashes, Some(vec![tag])).await?;
+    }
+    for (tag, hashes) in to_add {
+        client.add_torrent_tags(hashes, vec![tag]).await?;
+    }
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed_tag_default_buckets() {
+        let config = KeeperConfig::default();
+        assert_eq!(config.seed_tag(0), "rt-seeds:0");
+        assert_eq!(config.seed_tag(1), "rt-seeds:1-3");
+        assert_eq!(config.seed_tag(3), "rt-seeds:1-3");
+        assert_eq!(config.seed_tag(4), "rt-seeds:4+");
+        assert_eq!(config.seed_tag(100), "rt-seeds:4+");
+    }
+
+    #[test]
+    fn test_seed_tag_custom_buckets() {
+        let config = KeeperConfig {
+            thresholds: vec![2, 3, 10],
+            ..Default::default()
+        };
+        assert_eq!(config.seed_tag(1), "rt-seeds:0-1");
+        assert_eq!(config.seed_tag(2), "rt-seeds:2");
+        assert_eq!(config.seed_tag(9), "rt-seeds:3-9");
+        assert_eq!(config.seed_tag(10), "rt-seeds:10+");
+    }
+
+    #[test]
+    fn test_tag_change_removes_stale() {
+        assert_eq!(
+            tag_change("hd, rt-seeds:4+", "rt-seeds:0", "rt-seeds:"),
+            TagChange {
+                add: Some("rt-seeds:0".into()),
+                remove: vec!["rt-seeds:4+".into()],
+            }
+        );
+        assert_eq!(
+            tag_change("rt-seeds:0, hd", "rt-seeds:0", "rt-seeds:"),
+            TagChange {
+                add: None,
+                remove: vec![],
+            }
+        );
+        assert_eq!(
+            tag_change("", "rt-seeds:1-3", "rt-seeds:").add.as_deref(),
+            Some("rt-seeds:1-3")
+        );
+    }
+}
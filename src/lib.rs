@@ -4,46 +4,121 @@
 //! Модуль для работы с торрентами и API rutracker.cc
 //!
 
+pub mod cache;
+pub mod connection;
+pub mod control;
+pub mod disk_space;
+pub mod http;
+pub mod ignore_list;
+pub mod interactive;
+pub mod keep_previous;
+pub mod keeper;
+pub mod lock;
+pub mod logging;
+pub mod metainfo;
+pub mod metrics;
+pub mod paths;
+pub mod pending;
+pub mod plan;
+pub mod recheck;
+pub mod report;
+pub mod rules;
 pub mod rutracker_api;
+pub mod shutdown;
+pub mod topic_id;
+pub mod topic_info;
 pub mod torrent;
+pub mod trackers;
+pub mod updater;
 
 use anyhow::{Context, Result};
+use cache::{CachedProperties, PropertiesCache};
+use connection::ConnectionConfig;
+use control::{Control, ControlConfig, TorrentStatus};
+use disk_space::{DiskBudget, FreeSpaceConfig, LowSpace, PresentFile};
+use futures::stream::{self, StreamExt};
+use http::{HttpClient, HttpConfig, ProxyConfig};
+use ignore_list::IgnoreList;
+use keep_previous::{KeepPreviousConfig, PreserveMode, Preserved, PreservedVersion};
+use keeper::KeeperConfig;
+use lock::InstanceLock;
+use metainfo::MetainfoFile;
+use metrics::MetricsConfig;
+use pending::{KeepOld, PendingUpdate, PendingUpdates, Step, TwoPhaseConfig};
+use plan::{Plan, PlanEntry, PlanMismatch};
 use qbit_rs::{
-    model::{AddTorrentArg, Credential, TorrentFile, TorrentSource},
+    model::{AddTorrentArg, GetTorrentListArg, TorrentFile, TorrentSource},
     Qbit,
 };
-use rutracker_api::{
-    extract_torrent_id_from_comment, get_api_limit_async, get_api_peer_stats_by_hash_async,
-    get_api_torrent_hash_by_id_async,
-};
-use std::collections::HashMap;
+use recheck::{Recheck, RecheckConfig};
+use report::{topic_changes, ReportAction, ReportEntry, RunReport};
+use rules::{default_rules, Decision, Rule, RuleAction};
+use shutdown::{Shutdown, TempFile};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use topic_id::TopicId;
 use torrent::Torrent;
+use trackers::{find_provider, CheckOutcome, NnmClubProvider, RutrackerProvider, TrackerProvider};
+use updater::{Events, TorrentRef, Updater, UpdaterEvent};
 
-use reqwest::header::{HeaderMap, HeaderValue, COOKIE, USER_AGENT};
-use reqwest::Client;
 use serde::Deserialize;
 use tokio::fs;
 
+/// Инициализирует логгер с настройками по умолчанию (см. `logging::init`)
 pub fn init_logger() {
-    match log4rs::init_file("log4rs.yaml", Default::default()) {
-        Ok(_) => log::debug!("log4rs.yaml загружен, логгер инициализирован."),
-        Err(e) => log::error!(
-            "❌ Ошибка инициализации log4rs (файл log4rs.yaml не найден?): {}",
-            e
-        ),
+    let yaml = Path::new(logging::LOG4RS_CONFIG);
+    if let Err(e) = logging::init(&logging::LogOptions::default(), yaml) {
+        eprintln!("{:#}", e);
     }
 }
 
 #[derive(Deserialize, Debug)]
 pub struct QbitConfig {
     pub url: String,
+    /// Логин WebUI. Пустой — входить без пароля (обход авторизации для localhost).
+    #[serde(default)]
     pub username: String,
+    #[serde(default)]
     pub password: String,
+    /// TLS, basic auth и заголовки для обратного прокси
+    #[serde(flatten)]
+    pub connection: ConnectionConfig,
+    /// Сколько запросов свойств торрентов выполнять одновременно
+    #[serde(default = "default_properties_concurrency")]
+    pub properties_concurrency: usize,
+}
+
+fn default_properties_concurrency() -> usize {
+    8
+}
+
+fn default_cache_path() -> String {
+    "cache/properties.json".to_string()
+}
+
+fn default_ignore_path() -> String {
+    "cache/ignored.json".to_string()
 }
 
 #[derive(Deserialize, Debug)]
 pub struct RutrackerConfig {
     pub bb_session_cookie: String,
+    /// Прокси для всех запросов к Rutracker (вместо `http.proxy`)
+    pub proxy: Option<ProxyConfig>,
+    /// Прокси только для api.rutracker.cc (вместо `proxy`)
+    pub api_proxy: Option<ProxyConfig>,
+    /// Прокси только для скачивания .torrent через dl.php (вместо `proxy`)
+    pub download_proxy: Option<ProxyConfig>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NnmClubConfig {
+    /// Строка cookie авторизованной сессии форума
+    pub cookie: String,
+    /// Прокси для запросов к NNM-Club (вместо `http.proxy`)
+    pub proxy: Option<ProxyConfig>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -51,45 +126,277 @@ pub struct Config {
     pub dry_run: bool,
     pub qbit: QbitConfig,
     pub rutracker: RutrackerConfig,
+    pub nnmclub: Option<NnmClubConfig>,
+    #[serde(default)]
+    pub http: HttpConfig,
+    /// Обрабатывать только торренты с этим тегом (фильтр на стороне qBittorrent)
     pub tag_filter: Option<String>,
+    /// Обрабатывать только торренты этой категории (фильтр на стороне qBittorrent)
+    pub category_filter: Option<String>,
+    /// Файл кеша свойств торрентов между запусками
+    #[serde(default = "default_cache_path")]
+    pub cache_path: String,
+    /// Куда сохранять JSON-отчет о запуске (не сохраняется, если не задано)
+    pub report_path: Option<String>,
+    /// Подтверждать каждое действие в терминале (включается флагом `--interactive`)
+    #[serde(default)]
+    pub interactive: bool,
+    /// Торренты, навсегда исключенные из обработки в интерактивном режиме
+    #[serde(default = "default_ignore_path")]
+    pub ignore_path: String,
+    /// Режим хранителя: теги по числу сидов
+    #[serde(default)]
+    pub keeper: KeeperConfig,
+    /// Сообщать о раздачах, на которых нет сидов дольше этого числа дней
+    pub no_seeders_report_days: Option<u64>,
+    /// Правила обработки торрентов (`[[rules]]`). Если не заданы, действуют правила по умолчанию.
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    /// Метрики Prometheus
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    /// HTTP API управления для фонового режима
+    #[serde(default)]
+    pub control: ControlConfig,
+    /// Контроль проверки данных после обновления
+    #[serde(default)]
+    pub recheck: RecheckConfig,
+    /// Проверка свободного места перед обновлением
+    #[serde(default)]
+    pub free_space: FreeSpaceConfig,
+    /// Двухфазное обновление: старый торрент удаляется, когда новая версия докачается
+    #[serde(default)]
+    pub two_phase: TwoPhaseConfig,
+    /// Сохранение предыдущей версии файлов перед обновлением
+    #[serde(default)]
+    pub keep_previous: KeepPreviousConfig,
 }
 
-pub async fn run_helper(config: Config) -> Result<()> {
-    let client_for_download = Client::builder().build()?;
+impl Config {
+    /// Проверяет сочетания настроек, которые нельзя выразить типами
+    pub fn validate(&self) -> Result<()> {
+        self.two_phase.validate()?;
+        let keep = &self.keep_previous;
+        if keep.enabled && keep.mode == PreserveMode::Move && self.two_phase.seeds_from_same_path()
+        {
+            anyhow::bail!(
+                "❌ [keep_previous]: mode = \"move\" забирает файлы у старой версии, которая \
+                 продолжает раздаваться ([two_phase] keep_old = \"seed\"). Используйте mode = \"copy\""
+            );
+        }
+        Ok(())
+    }
+
+    /// Разрешает относительные пути из конфигурации относительно папки `base_dir`
+    /// (папки файла конфигурации), чтобы они не зависели от текущей папки
+    pub fn resolve_paths(&mut self, base_dir: &Path) {
+        let resolve = |path: &mut String| *path = paths::resolve(base_dir, path);
+
+        resolve(&mut self.cache_path);
+        resolve(&mut self.ignore_path);
+        self.report_path.iter_mut().for_each(resolve);
+        self.metrics.textfile_path.iter_mut().for_each(resolve);
+        self.qbit.connection.ca_cert.iter_mut().for_each(resolve);
+        resolve(&mut self.recheck.archive_dir);
+        resolve(&mut self.two_phase.state_path);
+        resolve(&mut self.keep_previous.dir);
+    }
+
+    /// Правила из конфигурации или правила по умолчанию
+    pub fn effective_rules(&self) -> Vec<Rule> {
+        if self.rules.is_empty() {
+            default_rules()
+        } else {
+            self.rules.clone()
+        }
+    }
+}
+
+/// Создает список трекеров, включенных в конфигурации
+///
+/// Все трекеры используют HTTP-клиент `http` (пул соединений, таймауты, ограничитель частоты,
+/// статистика запросов). Для трекеров со своим прокси создается клиент с тем же ограничителем.
+pub fn build_providers(
+    config: &Config,
+    http: &HttpClient,
+) -> Result<Vec<Box<dyn TrackerProvider>>> {
+    let rutracker = &config.rutracker;
+    let api_http = http_for(
+        http,
+        rutracker.api_proxy.as_ref().or(rutracker.proxy.as_ref()),
+    )?;
+    let download_http = http_for(
+        http,
+        rutracker
+            .download_proxy
+            .as_ref()
+            .or(rutracker.proxy.as_ref()),
+    )?;
+
+    let mut providers: Vec<Box<dyn TrackerProvider>> = vec![Box::new(
+        RutrackerProvider::new(api_http, &rutracker.bb_session_cookie)?
+            .with_download_http(download_http),
+    )];
+
+    if let Some(nnmclub) = &config.nnmclub {
+        providers.push(Box::new(NnmClubProvider::new(
+            http_for(http, nnmclub.proxy.as_ref())?,
+            &nnmclub.cookie,
+        )?));
+    }
+
+    Ok(providers)
+}
 
-    let mut headers = HeaderMap::new();
-    let cookie_string = format!("bb_session={}", config.rutracker.bb_session_cookie);
-    headers.insert(COOKIE, HeaderValue::from_str(&cookie_string)?);
-    headers.insert(USER_AGENT, HeaderValue::from_static("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/100.0.0.0 Safari/537.36"));
+/// Общий клиент или клиент с прокси, заданным для конкретного трекера
+fn http_for(http: &HttpClient, proxy: Option<&ProxyConfig>) -> Result<HttpClient> {
+    match proxy {
+        Some(proxy) => http.with_proxy(proxy),
+        None => Ok(http.clone()),
+    }
+}
+
+/// Режим обработки результатов проверки
+enum Mode<'a> {
+    /// Сразу выполнить действия (или только показать их при dry_run)
+    Run,
+    /// Сохранить действия в файл плана, ничего не меняя
+    Plan(&'a Path),
+}
 
+async fn connect(config: &Config) -> Result<Qbit> {
     log::info!("Подключение к {}...", config.qbit.url);
 
-    let credential = Credential::new(config.qbit.username.clone(), config.qbit.password.clone());
-    let client = Qbit::new(config.qbit.url.as_str(), credential);
+    let qbit = &config.qbit;
+    connection::connect(&qbit.url, &qbit.username, &qbit.password, &qbit.connection)
+        .await
+        .context("❌ Не удалось подключиться к qBittorrent")
+}
 
-    if let Err(e) = process_torrents(
-        &client,
-        &client_for_download,
-        &headers,
-        config.dry_run,
-        config.tag_filter,
-    )
-    .await
-    {
-        return Err(e).context("❌ Ошибка при обработке торрентов. Убедитесь, что qBittorrent запущен и учетные данные верны.");
+/// Временные .torrent файлы старше этого срока остались от прерванных запусков
+const STALE_DOWNLOAD_AGE: Duration = Duration::from_secs(60 * 60);
+
+const QBIT_ERROR_HINT: &str =
+    "❌ Ошибка при обработке торрентов. Убедитесь, что qBittorrent запущен и учетные данные верны.";
+
+pub async fn run_helper(config: Config) -> Result<()> {
+    // Один запуск на экземпляр qBittorrent: блокировка держится до конца функции
+    let _lock = InstanceLock::acquire(&config.qbit.url)?;
+    let updater = Updater::builder(config)
+        .shutdown(Shutdown::listen())
+        .build()
+        .await?;
+    shutdown::remove_stale_files(&paths::download_dir(), STALE_DOWNLOAD_AGE);
+
+    updater.run().await.map(|_| ())
+}
+
+/// Проверяет торренты каждые `interval`, пока процесс не остановят.
+///
+/// Ошибка отдельного запуска не прерывает работу: она попадает в лог и метрики.
+pub async fn daemon_helper(config: Config, interval: Duration) -> Result<()> {
+    let _lock = InstanceLock::acquire(&config.qbit.url)?;
+    let control = Arc::new(Control::default());
+    let updater = Updater::builder(config)
+        .shutdown(Shutdown::listen())
+        .control(control.clone())
+        .build()
+        .await?;
+    shutdown::remove_stale_files(&paths::download_dir(), STALE_DOWNLOAD_AGE);
+
+    let config = updater.config();
+    if let Some(listen) = &config.metrics.listen {
+        metrics::serve(listen, updater.metrics().clone()).await?;
     }
+    control::serve(&config.control, control.clone()).await?;
 
+    log::info!(
+        "🔁 Фоновый режим: проверка каждые {} мин.",
+        interval.as_secs() / 60
+    );
+    while !updater.is_stopped() {
+        if let Err(e) = updater.run().await {
+            log::error!("❌ Запуск завершился ошибкой: {:?}", e);
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = control.run_requested() => {}
+            _ = updater.stopped() => {}
+        }
+    }
+
+    log::info!("⏹️ Фоновый режим остановлен");
     Ok(())
 }
 
+/// Проверяет торренты и сохраняет план действий в `output`, ничего не меняя в qBittorrent
+pub async fn plan_helper(config: Config, output: &Path) -> Result<()> {
+    let updater = Updater::builder(config)
+        .shutdown(Shutdown::listen())
+        .build()
+        .await?;
+
+    updater.plan(output).await.map(|_| ())
+}
+
+/// Выполняет сохраненный план, отклоняя действия, для которых что-то изменилось
+pub async fn apply_helper(config: Config, plan_path: &Path) -> Result<()> {
+    let plan = Plan::load(plan_path)?;
+    let _lock = InstanceLock::acquire(&config.qbit.url)?;
+    let updater = Updater::builder(config)
+        .shutdown(Shutdown::listen())
+        .build()
+        .await?;
+    shutdown::remove_stale_files(&paths::download_dir(), STALE_DOWNLOAD_AGE);
+
+    updater.apply(plan).await.map(|_| ())
+}
+
+/// Итог одного запуска проверки
+struct RunResult {
+    report: RunReport,
+    /// Проверенные торренты со статистикой с трекера
+    torrents: Vec<TorrentStatus>,
+}
+
+/// Проверяет торренты и выполняет (или планирует) действия
 async fn process_torrents(
     client: &Qbit,
-    client_for_download: &Client,
-    headers: &HeaderMap,
-    dry_run: bool,
-    tag_filter: Option<String>,
-) -> Result<()> {
-    let mut my_torrents = match get_qbit_torrents(client, tag_filter).await {
+    providers: &[Box<dyn TrackerProvider>],
+    config: &Config,
+    mode: Mode<'_>,
+    shutdown: &Shutdown,
+    events: &Events,
+) -> Result<RunResult> {
+    // При составлении плана ничего не меняем, как в пробном запуске
+    let dry_run = config.dry_run || matches!(mode, Mode::Plan(_));
+    let cache_path = Path::new(&config.cache_path);
+    let mut cache = PropertiesCache::load(cache_path);
+    let mut report = RunReport::new(dry_run);
+    let mut plan = Plan::default();
+    let rules = config.effective_rules();
+    let ignore_path = Path::new(&config.ignore_path);
+    let mut ignore = IgnoreList::load(ignore_path)?;
+    let interactive = config.interactive && matches!(mode, Mode::Run);
+    let mut checked = Vec::new();
+    let disk = DiskBudget::new(&config.free_space);
+    let pending = Mutex::new(PendingUpdates::load(Path::new(
+        &config.two_phase.state_path,
+    ))?);
+    let rechecks = Mutex::new(Vec::new());
+    let ctx = ActionContext {
+        client,
+        config,
+        dry_run,
+        shutdown,
+        events,
+        disk: &disk,
+        pending: &pending,
+        rechecks: &rechecks,
+    };
+
+    let mut my_torrents = match get_qbit_torrents(client, providers, config, &mut cache).await {
         Ok(torrents) => torrents,
         Err(e) => {
             log::error!("❌ Ошибка при получении списка торрентов: {}", e);
@@ -97,51 +404,373 @@ async fn process_torrents(
         }
     };
 
+    // Торренты незавершенных обновлений не проверяем: у старого новая версия уже добавлена,
+    // а новый, обновленный еще раз, оставил бы старый торрент в клиенте навсегда
+    let staged = pending_hashes(&ctx);
+    my_torrents.retain(|t| !staged.contains(&t.torrent_hash));
+    finish_pending_updates(&ctx, &mut report).await;
+
+    if events.is_enabled() {
+        for torrent in &my_torrents {
+            if let Some(provider) = find_provider(providers, &torrent.tracker) {
+                events
+                    .emit(UpdaterEvent::TorrentDiscovered(TorrentRef::new(
+                        provider.name(),
+                        torrent,
+                    )))
+                    .await;
+            }
+        }
+    }
+
     if my_torrents.is_empty() {
-        log::info!("Торрентов c Rutracker не найдено. Завершение работы.");
-        return Ok(());
+        log::info!("Торрентов с поддерживаемых трекеров не найдено. Завершение работы.");
+        return Ok(RunResult {
+            report,
+            torrents: checked,
+        });
     }
 
-    log::info!(
-        "Найдено {} торрентов с Rutracker. Поиск обновлений...",
-        my_torrents.len()
-    );
+    for provider in providers {
+        if shutdown.is_requested() {
+            break;
+        }
 
-    // Запрашиваем лимит один раз для всех последующих запросов API!
-    let api_limit = match get_api_limit_async().await {
-        Ok(lim) => (lim as usize).min(50),
-        Err(e) => {
-            log::warn!("⚠️ Не удалось получить лимит API, используем 20: {}", e);
-            20
+        let mut provider_torrents: Vec<Torrent> = my_torrents
+            .iter()
+            .filter(|t| provider.recognizes(&t.tracker))
+            .cloned()
+            .collect();
+
+        if provider_torrents.is_empty() {
+            continue;
+        }
+
+        log::info!(
+            "Найдено {} торрентов с {}. Поиск обновлений...",
+            provider_torrents.len(),
+            provider.name()
+        );
+
+        // Проверка ничего не меняет, поэтому ее можно прервать в любой момент
+        let outcomes = tokio::select! {
+            outcomes = provider.check_torrents(&mut provider_torrents) => outcomes,
+            _ = shutdown.requested() => break,
+        };
+        report.checked += outcomes.len();
+
+        for torrent in &provider_torrents {
+            if let Some(topic) = &torrent.topic_info {
+                cache.set_topic(&torrent.torrent_hash, topic.clone());
+            }
+            if provider.reports_peer_stats()
+                && outcomes.get(&torrent.torrent_hash) == Some(&CheckOutcome::Current)
+            {
+                *report
+                    .seeders
+                    .entry(config.keeper.seed_group(torrent.seeders))
+                    .or_default() += 1;
+                events
+                    .emit(UpdaterEvent::StatsUpdated(TorrentStatus::new(
+                        provider.name(),
+                        torrent,
+                    )))
+                    .await;
+            }
+        }
+
+        if config.keeper.enabled && provider.reports_peer_stats() {
+            // Теги ставим только торрентам, статистика которых получена в этом запуске
+            let checked: Vec<&Torrent> = provider_torrents
+                .iter()
+                .filter(|t| outcomes.get(&t.torrent_hash) == Some(&CheckOutcome::Current))
+                .collect();
+            match keeper::apply_seed_tags(client, &config.keeper, &checked, dry_run).await {
+                Ok(0) => log::debug!("Теги хранителя актуальны."),
+                Ok(changed) => log::info!(
+                    "{}🏷️ Теги по числу сидов обновлены у {} торрентов",
+                    if dry_run { "(Dry Run) " } else { "" },
+                    changed
+                ),
+                Err(e) => log::warn!("⚠️ Не удалось обновить теги хранителя: {:#}", e),
+            }
+        }
+
+        let mut entries = plan_torrents(
+            provider.name(),
+            &provider_torrents,
+            &outcomes,
+            &rules,
+            &ignore,
+            dry_run,
+            &mut report,
+        );
+
+        let by_hash: HashMap<&str, &Torrent> = provider_torrents
+            .iter()
+            .map(|t| (t.torrent_hash.as_str(), t))
+            .collect();
+        let mut downloaded = HashMap::new();
+        if interactive && !entries.is_empty() && !shutdown.is_requested() {
+            let reviewed =
+                interactive::review(client, provider.as_ref(), &by_hash, entries, &mut ignore)
+                    .await?;
+            entries = reviewed.entries;
+            downloaded = reviewed.metainfo;
+        }
+        for entry in &entries {
+            if let Some(torrent) = by_hash.get(entry.hash.as_str()) {
+                if shutdown.is_requested() {
+                    interrupt_entry(provider.name(), entry, Some(torrent), &mut report);
+                    continue;
+                }
+                let metainfo = downloaded.remove(&entry.hash);
+                execute_entry(
+                    &ctx,
+                    provider.as_ref(),
+                    torrent,
+                    entry,
+                    metainfo,
+                    &mut report,
+                )
+                .await;
+            }
         }
+        plan.entries.extend(entries);
+
+        if let Some(days) = config.no_seeders_report_days {
+            report_torrents_without_seeders(
+                provider.name(),
+                &provider_torrents,
+                &outcomes,
+                days,
+                &mut report,
+            );
+        }
+
+        checked.extend(
+            provider_torrents
+                .iter()
+                .map(|t| TorrentStatus::new(provider.name(), t)),
+        );
+    }
+
+    wait_rechecks(&ctx, &mut report).await;
+    if !dry_run {
+        prune_versions(&config.keep_previous);
+    }
+    report.interrupted = shutdown.is_requested();
+    log_summary(&report, config);
+
+    if interactive {
+        ignore.save(ignore_path)?;
+    }
+
+    if let Mode::Plan(output) = mode {
+        plan.save(output)?;
+        log::info!(
+            "📝 План сохранен в {} (действий: {}). Выполнить: apply {}",
+            output.display(),
+            plan.entries.len(),
+            output.display()
+        );
+    }
+
+    if let Err(e) = cache.save(cache_path) {
+        log::warn!("⚠️ {:#}", e);
+    }
+
+    save_report(&report, config);
+
+    Ok(RunResult {
+        report,
+        torrents: checked,
+    })
+}
+
+/// Выполняет план: каждое действие сверяется с текущим состоянием qBittorrent и ответом трекера
+async fn apply_plan(
+    client: &Qbit,
+    providers: &[Box<dyn TrackerProvider>],
+    config: &Config,
+    plan: Plan,
+    shutdown: &Shutdown,
+    events: &Events,
+) -> Result<RunReport> {
+    let dry_run = config.dry_run;
+    let cache_path = Path::new(&config.cache_path);
+    let mut cache = PropertiesCache::load(cache_path);
+    let mut report = RunReport::new(dry_run);
+    let disk = DiskBudget::new(&config.free_space);
+    let pending = Mutex::new(PendingUpdates::load(Path::new(
+        &config.two_phase.state_path,
+    ))?);
+    let rechecks = Mutex::new(Vec::new());
+    let ctx = ActionContext {
+        client,
+        config,
+        dry_run,
+        shutdown,
+        events,
+        disk: &disk,
+        pending: &pending,
+        rechecks: &rechecks,
     };
 
-    log::debug!("--- Обновление статистики (сиды/личи) с Rutracker ---");
-    let problematic_ids = get_api_peer_stats_by_hash_async(&mut my_torrents, api_limit).await?;
-    log::debug!("✅ Статистика успешно обновлена.");
+    log::info!("📝 Выполнение плана: {} действий", plan.entries.len());
+
+    let current: HashMap<String, Torrent> =
+        get_qbit_torrents(client, providers, config, &mut cache)
+            .await?
+            .into_iter()
+            .map(|t| (t.torrent_hash.clone(), t))
+            .collect();
+
+    // Как и при обычном запуске: сначала завершаем двухфазные обновления, а их старые и новые
+    // торренты не трогаем, даже если они есть в плане
+    let staged = pending_hashes(&ctx);
+    finish_pending_updates(&ctx, &mut report).await;
+
+    for provider in providers {
+        if shutdown.is_requested() {
+            break;
+        }
+
+        let mut accepted: Vec<&PlanEntry> = Vec::new();
+        let mut torrents: Vec<Torrent> = Vec::new();
+
+        for entry in plan.entries.iter().filter(|e| e.tracker == provider.name()) {
+            let torrent = current.get(&entry.hash);
+            let verified = if staged.contains(&entry.hash) {
+                Err(PlanMismatch::PendingUpdate)
+            } else {
+                entry.verify_client_state(torrent)
+            };
+            match verified {
+                Ok(()) => {
+                    accepted.push(entry);
+                    torrents.extend(torrent.cloned());
+                }
+                Err(mismatch) => {
+                    refuse_entry(provider.name(), entry, torrent, mismatch, &mut report)
+                }
+            }
+        }
+
+        if accepted.is_empty() {
+            continue;
+        }
+
+        log::info!(
+            "Повторная проверка {} торрентов на {}...",
+            torrents.len(),
+            provider.name()
+        );
+        let outcomes = tokio::select! {
+            outcomes = provider.check_torrents(&mut torrents) => outcomes,
+            _ = shutdown.requested() => break,
+        };
+        let by_hash: HashMap<&str, &Torrent> = torrents
+            .iter()
+            .map(|t| (t.torrent_hash.as_str(), t))
+            .collect();
 
-    let (updates_count, deletions_count) = if !problematic_ids.is_empty() {
+        for entry in accepted {
+            let torrent = by_hash.get(entry.hash.as_str()).copied();
+            if shutdown.is_requested() {
+                interrupt_entry(provider.name(), entry, torrent, &mut report);
+                continue;
+            }
+            match entry.verify_outcome(outcomes.get(&entry.hash)) {
+                Ok(()) => {
+                    if let Some(torrent) = torrent {
+                        execute_entry(&ctx, provider.as_ref(), torrent, entry, None, &mut report)
+                            .await;
+                    }
+                }
+                Err(mismatch) => {
+                    refuse_entry(provider.name(), entry, torrent, mismatch, &mut report)
+                }
+            }
+        }
+    }
+
+    for entry in plan
+        .entries
+        .iter()
+        .filter(|e| !providers.iter().any(|p| p.name() == e.tracker))
+    {
         log::warn!(
-            "--- ⚠️ Обнаружены проблемные торренты (не найдены на Rutracker): {} шт. ---",
-            problematic_ids.len()
+            "⚠️ Действие для '{}' пропущено: трекер {} не включен в конфигурации",
+            entry.name,
+            entry.tracker
         );
-        log::debug!("Запрос хешей для проблемных ID...");
+    }
 
-        let hashes_map = get_api_torrent_hash_by_id_async(&problematic_ids, api_limit).await?;
-        log::debug!("Получены хеши для {} ID. Анализ...", hashes_map.len());
+    wait_rechecks(&ctx, &mut report).await;
+    if !dry_run {
+        prune_versions(&config.keep_previous);
+    }
+    report.interrupted = shutdown.is_requested();
+    log_summary(&report, config);
 
-        handle_problematic_torrents(
-            client,
-            &my_torrents,
-            &hashes_map,
-            client_for_download,
-            headers,
-            dry_run,
-        )
-        .await?
-    } else {
-        (0, 0)
-    };
+    let refused = report.count(ReportAction::Refused);
+    if refused > 0 {
+        log::warn!(
+            "--- ⛔ Отклонено действий из плана: {} (составьте план заново) ---",
+            refused
+        );
+    }
+
+    save_report(&report, config);
+
+    Ok(report)
+}
+
+fn refuse_entry(
+    tracker: &str,
+    entry: &PlanEntry,
+    torrent: Option<&Torrent>,
+    mismatch: PlanMismatch,
+    report: &mut RunReport,
+) {
+    log::warn!(
+        "⛔ Действие '{}' для '{}' отклонено: {}",
+        entry.action,
+        entry.name,
+        mismatch
+    );
+
+    let mut report_entry = ReportEntry::from_plan(tracker, entry, ReportAction::Refused);
+    report_entry.old_topic = torrent.and_then(|t| t.topic_info.clone());
+    report_entry.error = Some(mismatch.to_string());
+    report.push(report_entry);
+}
+
+/// Записывает в отчет действие, которое не начато из-за остановки
+fn interrupt_entry(
+    tracker: &str,
+    entry: &PlanEntry,
+    torrent: Option<&Torrent>,
+    report: &mut RunReport,
+) {
+    log::info!(
+        "⏹️ Действие '{}' для '{}' не выполнено: работа останавливается",
+        entry.action,
+        entry.name
+    );
+
+    let mut report_entry = ReportEntry::from_plan(tracker, entry, ReportAction::Interrupted);
+    report_entry.old_topic = torrent.and_then(|t| t.topic_info.clone());
+    report.push(report_entry);
+}
+
+fn log_summary(report: &RunReport, config: &Config) {
+    let dry_run = report.dry_run;
+    let updates_count = report.count(ReportAction::Updated);
+    let deletions_count = report.count(ReportAction::Removed);
+    let unknown_count = report.count(ReportAction::Unknown);
+    let staged = report.count(ReportAction::Staged);
 
     if updates_count > 0 || deletions_count > 0 {
         if !dry_run {
@@ -157,19 +786,101 @@ async fn process_torrents(
                 deletions_count
             );
         }
-    } else {
+    } else if unknown_count == 0 && staged == 0 {
         log::info!("--- 📊 Сводка: Все торренты актуальны. Обновлений не найдено. ---");
     }
 
-    Ok(())
+    if staged > 0 {
+        log::info!(
+            "--- ⏳ Новых версий добавлено рядом со старыми: {} (старые удаляются после докачки) ---",
+            staged
+        );
+    }
+
+    let other_actions = report.count(ReportAction::Paused)
+        + report.count(ReportAction::Tagged)
+        + report.count(ReportAction::Notified);
+    if other_actions > 0 {
+        log::info!(
+            "--- 📊 По правилам: остановлено: {}, помечено тегом: {}, только уведомление: {} ---",
+            report.count(ReportAction::Paused),
+            report.count(ReportAction::Tagged),
+            report.count(ReportAction::Notified)
+        );
+    }
+
+    let failed = report.count(ReportAction::Failed);
+    if failed > 0 {
+        log::error!("--- ❌ Ошибок при выполнении действий: {} ---", failed);
+    }
+
+    let without_seeders = report.count(ReportAction::NoSeeders);
+    if without_seeders > 0 {
+        log::warn!(
+            "--- 🌱 Раздач без сидов дольше {} дн.: {} ---",
+            config.no_seeders_report_days.unwrap_or_default(),
+            without_seeders
+        );
+    }
+
+    let deferred = report.count(ReportAction::Deferred);
+    if deferred > 0 {
+        log::warn!(
+            "--- 💽 Отложено из-за нехватки места: {} шт. (будут повторены при следующем запуске) ---",
+            deferred
+        );
+    }
+
+    if unknown_count > 0 {
+        log::warn!(
+            "--- ⚠️ Не удалось проверить: {} шт. (будут проверены при следующем запуске) ---",
+            unknown_count
+        );
+    }
+
+    if report.interrupted {
+        log::warn!(
+            "--- ⏹️ Запуск остановлен по сигналу, не выполнено действий: {} (отчет неполный) ---",
+            report.count(ReportAction::Interrupted)
+        );
+    }
 }
 
-async fn get_qbit_torrents(client: &Qbit, tag_filter: Option<String>) -> Result<Vec<Torrent>> {
-    let torrents_info = client.get_torrent_list(Default::default()).await?;
-    log::debug!("--- Обработка торрентов ({} шт.) ---", torrents_info.len());
+/// Удаляет сохраненные версии данных сверх лимитов `[keep_previous]`
+fn prune_versions(config: &KeepPreviousConfig) {
+    if config.enabled {
+        let max_age = config
+            .max_age_days
+            .map(|days| Duration::from_secs(days * 24 * 60 * 60));
+        keep_previous::prune(Path::new(&config.dir), config.keep_versions, max_age);
+    }
+}
 
-    let mut my_torrents: Vec<Torrent> = Vec::new();
+fn save_report(report: &RunReport, config: &Config) {
+    if let Some(report_path) = &config.report_path {
+        match report.save(Path::new(report_path)) {
+            Ok(()) => log::info!("Отчет о запуске сохранен в {}", report_path),
+            Err(e) => log::warn!("⚠️ {:#}", e),
+        }
+    }
+}
+
+async fn get_qbit_torrents(
+    client: &Qbit,
+    providers: &[Box<dyn TrackerProvider>],
+    config: &Config,
+    cache: &mut PropertiesCache,
+) -> Result<Vec<Torrent>> {
+    // Фильтруем по тегу и категории на стороне qBittorrent, чтобы не тянуть весь список
+    let list_arg = GetTorrentListArg {
+        tag: config.tag_filter.clone(),
+        category: config.category_filter.clone(),
+        ..Default::default()
+    };
+    let torrents_info = client.get_torrent_list(list_arg).await?;
+    log::debug!("--- Обработка торрентов ({} шт.) ---", torrents_info.len());
 
+    let mut candidates = Vec::new();
     for torrent_info in torrents_info.iter() {
         let name = torrent_info.name.clone().unwrap_or_default();
         // СРАЗУ приводим хеш к нижнему регистру, чтобы избежать аллокаций при поиске
@@ -181,162 +892,1089 @@ async fn get_qbit_torrents(client: &Qbit, tag_filter: Option<String>) -> Result<
         }
 
         let tracker = torrent_info.tracker.clone().unwrap_or_default();
-        if !tracker.contains("rutracker") {
+        let Some(provider) = find_provider(providers, &tracker) else {
             continue;
-        }
+        };
 
-        if let Some(ref target_tag) = tag_filter {
-            let current_tags = torrent_info.tags.as_deref().unwrap_or_default();
-            // Проверяем, содержит ли строка тегов наш целевой тег
-            if !current_tags.contains(target_tag) {
-                log::trace!("Пропущен (нет тега '{}'): {}", target_tag, name);
-                continue;
-            }
-        }
+        candidates.push((torrent_info, provider, name, hash, tracker));
+    }
 
-        let save_path = torrent_info.save_path.clone().unwrap_or_default();
+    // Свойства запрашиваем только для торрентов, которых нет в кеше, и параллельно
+    let missing: Vec<String> = candidates
+        .iter()
+        .filter(|(_, _, _, hash, _)| cache.get(hash).is_none())
+        .map(|(_, _, _, hash, _)| hash.clone())
+        .collect();
+
+    log::debug!(
+        "Свойства в кеше: {}, запрашиваем у qBittorrent: {}",
+        candidates.len() - missing.len(),
+        missing.len()
+    );
 
-        match client.get_torrent_properties(&hash).await {
-            Ok(properties) => {
-                let comment = properties.comment.clone().unwrap_or_default();
-                let torrent_id = extract_torrent_id_from_comment(&comment);
-                let state = torrent_info
-                    .state
-                    .as_ref()
-                    .map(|s| format!("{:?}", s))
-                    .unwrap_or_default();
-                let category = torrent_info.category.clone().unwrap_or_default();
-                let tags = torrent_info.tags.clone().unwrap_or_default();
-                let size = torrent_info.size.unwrap_or(0) as u64;
-
-                my_torrents.push(Torrent {
-                    name,
-                    torrent_hash: hash,
-                    torrent_id,
-                    tracker,
+    let fetched: Vec<(String, Result<String, qbit_rs::Error>)> = stream::iter(missing)
+        .map(|hash| async move {
+            let comment = client
+                .get_torrent_properties(&hash)
+                .await
+                .map(|p| p.comment.unwrap_or_default());
+            (hash, comment)
+        })
+        .buffered(config.qbit.properties_concurrency.max(1))
+        .collect()
+        .await;
+
+    for (hash, result) in fetched {
+        match result {
+            Ok(comment) => cache.insert(
+                hash,
+                CachedProperties {
                     comment,
-                    state,
-                    category,
-                    tags,
-                    size,
-                    seeders: 0,
-                    leechers: 0,
-                    save_path,
-                });
-            }
+                    topic: None,
+                },
+            ),
+            Err(e) => log::warn!("⚠️ Не удалось получить свойства для {}: {}", hash, e),
+        }
+    }
+
+    let mut my_torrents: Vec<Torrent> = Vec::new();
+
+    for (torrent_info, provider, name, hash, tracker) in candidates {
+        let Some(properties) = cache.get(&hash) else {
+            continue;
+        };
+
+        let comment = properties.comment.clone();
+        let topic_info = properties.topic.clone();
+        let torrent_id = match provider.extract_topic_id(&comment) {
+            Ok(id) => Some(id),
             Err(e) => {
-                log::warn!("⚠️ Не удалось получить свойства для {}: {}", name, e);
+                log::warn!("⚠️ Не удалось получить ID темы для '{}': {}", name, e);
+                None
             }
-        }
+        };
+        let state = torrent_info
+            .state
+            .as_ref()
+            .map(|s| format!("{:?}", s))
+            .unwrap_or_default();
+        let category = torrent_info.category.clone().unwrap_or_default();
+        let tags = torrent_info.tags.clone().unwrap_or_default();
+        let size = torrent_info.size.unwrap_or(0) as u64;
+        let save_path = torrent_info.save_path.clone().unwrap_or_default();
+
+        my_torrents.push(Torrent {
+            name,
+            torrent_hash: hash,
+            torrent_id,
+            tracker,
+            comment,
+            state,
+            category,
+            tags,
+            size,
+            seeders: 0,
+            leechers: 0,
+            save_path,
+            topic_info,
+        });
+    }
+
+    // Забываем торренты, которых больше нет в клиенте. При фильтрации по тегу/категории
+    // видна только часть списка, поэтому чистим кеш только по полному списку.
+    if config.tag_filter.is_none() && config.category_filter.is_none() {
+        let present: HashSet<String> = torrents_info
+            .iter()
+            .filter_map(|t| t.hash.as_ref().map(|h| h.to_lowercase()))
+            .collect();
+        cache.retain_hashes(&present);
     }
+
     Ok(my_torrents)
 }
 
-async fn handle_problematic_torrents(
-    client: &Qbit,
+/// Выбирает по правилам действия для проверенных торрентов, ничего не выполняя.
+/// Непроверенные торренты попадают только в отчет.
+fn plan_torrents(
+    tracker: &str,
     my_torrents: &[Torrent],
-    hashes_map: &HashMap<String, Option<String>>,
-    client_for_download: &Client,
-    headers: &HeaderMap,
+    outcomes: &HashMap<String, CheckOutcome>,
+    rules: &[Rule],
+    ignore: &IgnoreList,
     dry_run: bool,
-) -> Result<(u32, u32)> {
-    let mut updates_count = 0;
-    let mut deletions_count = 0;
-
-    for torrent in my_torrents.iter().filter(|t| !t.torrent_id.is_empty()) {
-        if let Some(hash_option) = hashes_map.get(&torrent.torrent_id) {
-            match hash_option {
-                Some(new_hash) => {
-                    if !new_hash.eq_ignore_ascii_case(&torrent.torrent_hash) {
-                        match handle_update(
-                            client,
-                            torrent,
-                            new_hash,
-                            client_for_download,
-                            headers,
-                            dry_run,
-                        )
-                        .await
-                        {
-                            Ok(_) => updates_count += 1,
-                            Err(e) => log::error!(
-                                "❌ Ошибка при обновлении торрента {}: {}",
-                                torrent.name,
-                                e
-                            ),
-                        }
-                    }
+    report: &mut RunReport,
+) -> Vec<PlanEntry> {
+    let now = report.started_at as i64;
+    let mut entries = Vec::new();
+
+    for torrent in my_torrents.iter() {
+        let Some(outcome) = outcomes.get(&torrent.torrent_hash) else {
+            continue;
+        };
+
+        if let CheckOutcome::Unknown { error } = outcome {
+            log::warn!(
+                "⚠️ Не удалось проверить торрент '{}' (ID: {}): {}",
+                torrent.name,
+                torrent.topic_id_str(),
+                error
+            );
+            let mut entry = ReportEntry::new(tracker, torrent, ReportAction::Unknown);
+            entry.error = Some(error.clone());
+            report.push(entry);
+            continue;
+        }
+
+        if ignore.contains(&torrent.torrent_hash) {
+            log::debug!("Торрент '{}' в списке игнорируемых", torrent.name);
+            continue;
+        }
+
+        let Some(Decision { rule, action }) = rules::decide(rules, torrent, outcome, now) else {
+            continue;
+        };
+
+        if dry_run || action != RuleAction::Ignore {
+            log::info!(
+                topic_id:% = torrent.topic_id_str(), hash = torrent.torrent_hash.as_str(), action:% = action, rule = rule.as_str();
+                "{}Торрент '{}' (ID: {}): правило '{}' → {}",
+                if dry_run { "(Dry Run) " } else { "" },
+                torrent.name,
+                torrent.topic_id_str(),
+                rule,
+                action
+            );
+        }
+
+        // Обновить торрент без ID темы невозможно: скачать новый .torrent неоткуда
+        if action == RuleAction::Ignore
+            || (action == RuleAction::Update && torrent.torrent_id.is_none())
+        {
+            continue;
+        }
+
+        let changes = match outcome {
+            CheckOutcome::Changed {
+                topic: Some(topic), ..
+            } => topic_changes(torrent, topic),
+            _ => Vec::new(),
+        };
+        entries.push(PlanEntry::new(
+            tracker, torrent, outcome, action, rule, changes,
+        ));
+    }
+
+    entries
+}
+
+/// Все, что нужно для выполнения действий в одном запуске
+struct ActionContext<'a> {
+    client: &'a Qbit,
+    config: &'a Config,
+    dry_run: bool,
+    shutdown: &'a Shutdown,
+    events: &'a Events,
+    /// Свободное место с учетом уже добавленных в этом запуске обновлений
+    disk: &'a DiskBudget,
+    /// Незавершенные двухфазные обновления
+    pending: &'a Mutex<PendingUpdates>,
+    /// Проверки данных, которых дожидаемся после всех действий запуска
+    rechecks: &'a Mutex<Vec<RecheckWait>>,
+}
+
+/// Выполняет одно действие и записывает результат в отчет. `metainfo` — уже скачанный
+/// .torrent новой версии (после интерактивного сравнения файлов).
+async fn execute_entry(
+    ctx: &ActionContext<'_>,
+    provider: &dyn TrackerProvider,
+    torrent: &Torrent,
+    entry: &PlanEntry,
+    metainfo: Option<TempFile>,
+    report: &mut RunReport,
+) {
+    let ActionContext {
+        client,
+        dry_run,
+        events,
+        ..
+    } = *ctx;
+    let report_action = match &entry.action {
+        RuleAction::Update if ctx.config.two_phase.enabled => ReportAction::Staged,
+        RuleAction::Update => ReportAction::Updated,
+        RuleAction::Delete => ReportAction::Removed,
+        RuleAction::Pause => ReportAction::Paused,
+        RuleAction::Tag(_) => ReportAction::Tagged,
+        RuleAction::Notify => ReportAction::Notified,
+        RuleAction::Ignore => return,
+    };
+    let mut report_entry = ReportEntry::new(provider.name(), torrent, report_action);
+    report_entry.rule = Some(entry.rule.clone());
+    report_entry.new_hash = entry.new_hash.clone();
+    report_entry.new_topic = entry.new_topic.clone();
+    report_entry.changes = entry.changes.clone();
+
+    let torrent_ref = TorrentRef::new(provider.name(), torrent);
+    let mut recheck_wait = None;
+    if entry.action == RuleAction::Update && !dry_run {
+        events
+            .emit(UpdaterEvent::UpdateStarted {
+                torrent: torrent_ref.clone(),
+                new_hash: entry.new_hash.clone().unwrap_or_default(),
+            })
+            .await;
+    }
+
+    let result = match &entry.action {
+        RuleAction::Update => match (torrent.torrent_id, entry.new_hash.as_deref()) {
+            (Some(topic_id), Some(new_hash)) => handle_update(
+                ctx,
+                provider,
+                torrent,
+                topic_id,
+                new_hash,
+                &entry.changes,
+                metainfo,
+            )
+            .await
+            .map(|outcome| {
+                recheck_wait = outcome.recheck;
+                report_entry.preserved = outcome.preserved;
+            }),
+            _ => Err(anyhow::anyhow!("нет ID темы или нового хеша")),
+        },
+        RuleAction::Delete => handle_deletion(client, torrent, dry_run).await.map(|_| ()),
+        RuleAction::Pause => handle_pause(client, torrent, dry_run).await,
+        RuleAction::Tag(tag) => handle_tag(client, torrent, tag, dry_run).await,
+        RuleAction::Notify | RuleAction::Ignore => {
+            log::warn!(
+                "🔔 Торрент '{}' (ID: {}): {}{}",
+                torrent.name,
+                torrent.topic_id_str(),
+                entry.reason,
+                if entry.changes.is_empty() {
+                    String::new()
+                } else {
+                    format!(". Изменения в теме: {}", entry.changes.join("; "))
                 }
-                None => match handle_deletion(client, torrent, dry_run).await {
-                    Ok(_) => deletions_count += 1,
-                    Err(e) => {
-                        log::error!("❌ Ошибка при удалении торрента {}: {}", torrent.name, e)
-                    }
-                },
+            );
+            Ok(())
+        }
+    };
+
+    let low_space = result
+        .as_ref()
+        .err()
+        .and_then(|e| e.downcast_ref::<LowSpace>());
+
+    if let Some(low) = low_space {
+        log::warn!(
+            topic_id:% = torrent.topic_id_str(), hash = torrent.torrent_hash.as_str(), action:% = entry.action;
+            "💽 Обновление '{}' отложено до следующего запуска: {}",
+            torrent.name,
+            low
+        );
+        report_entry.action = ReportAction::Deferred;
+        report_entry.error = Some(low.to_string());
+        events
+            .emit(UpdaterEvent::Error {
+                torrent: Some(torrent_ref),
+                action: Some(entry.action.to_string()),
+                message: low.to_string(),
+            })
+            .await;
+    } else if let Err(e) = &result {
+        log::error!(
+            topic_id:% = torrent.topic_id_str(), hash = torrent.torrent_hash.as_str(), action:% = entry.action;
+            "❌ Ошибка при выполнении действия '{}' для торрента {}: {}",
+            entry.action,
+            torrent.name,
+            e
+        );
+        report_entry.action = ReportAction::Failed;
+        report_entry.error = Some(format!("{:#}", e));
+        events
+            .emit(UpdaterEvent::Error {
+                torrent: Some(torrent_ref),
+                action: Some(entry.action.to_string()),
+                message: format!("{:#}", e),
+            })
+            .await;
+    } else if !dry_run {
+        match entry.action {
+            RuleAction::Update if report_entry.action == ReportAction::Staged => {
+                events
+                    .emit(UpdaterEvent::UpdateStaged {
+                        torrent: torrent_ref,
+                        new_hash: entry.new_hash.clone().unwrap_or_default(),
+                    })
+                    .await;
             }
+            RuleAction::Update => {
+                events
+                    .emit(UpdaterEvent::UpdateFinished {
+                        torrent: torrent_ref,
+                        new_hash: entry.new_hash.clone().unwrap_or_default(),
+                    })
+                    .await;
+            }
+            RuleAction::Delete => events.emit(UpdaterEvent::Removed(torrent_ref)).await,
+            _ => {}
+        }
+    }
+    if let Some(mut wait) = recheck_wait {
+        wait.entry = report.entries.len();
+        ctx.rechecks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(wait);
+    }
+    report.push(report_entry);
+}
+
+/// Добавляет в отчет актуальные раздачи, на которых нет сидов дольше `min_days` дней
+fn report_torrents_without_seeders(
+    tracker: &str,
+    my_torrents: &[Torrent],
+    outcomes: &HashMap<String, CheckOutcome>,
+    min_days: u64,
+    report: &mut RunReport,
+) {
+    let now = report.started_at as i64;
+    for torrent in my_torrents {
+        if outcomes.get(&torrent.torrent_hash) != Some(&CheckOutcome::Current) {
+            continue;
         }
+        let Some(days) = torrent
+            .days_without_seeders(now)
+            .filter(|days| *days >= min_days)
+        else {
+            continue;
+        };
+
+        log::warn!(
+            "🌱 Нет сидов {} дн.: '{}' (ID: {}, личей: {})",
+            days,
+            torrent.name,
+            torrent.topic_id_str(),
+            torrent.leechers
+        );
+        let mut entry = ReportEntry::new(tracker, torrent, ReportAction::NoSeeders);
+        entry.days_without_seeders = Some(days);
+        report.push(entry);
     }
-    Ok((updates_count, deletions_count))
 }
 
+/// Итог замены торрента для отчета
+#[derive(Default)]
+struct UpdateOutcome {
+    /// Проверка данных нового торрента, которую нужно дождаться
+    recheck: Option<RecheckWait>,
+    /// Папка с сохраненной предыдущей версией файлов
+    preserved: Option<String>,
+}
+
+/// Заменяет торрент новой версией раздачи. Возвращает `UpdateOutcome`: проверку данных, которую
+/// нужно дождаться (если она включена), и папку сохраненной предыдущей версии. `metainfo` —
+/// уже скачанный .torrent новой версии; без него файл скачивается с трекера.
 async fn handle_update(
-    client: &Qbit,
+    ctx: &ActionContext<'_>,
+    provider: &dyn TrackerProvider,
     torrent: &Torrent,
+    topic_id: TopicId,
     new_hash: &str,
-    client_for_download: &Client,
-    headers: &HeaderMap,
-    dry_run: bool,
-) -> Result<bool> {
+    changes: &[String],
+    metainfo: Option<TempFile>,
+) -> Result<UpdateOutcome> {
+    let client = ctx.client;
     log::warn!(
-        "🔄 ОБНОВЛЕНИЕ: Торрент '{}' (ID: {}) обновлен на трекере.",
+        topic_id:% = topic_id, hash = torrent.torrent_hash.as_str(), new_hash, action = "update";
+        "🔄 ОБНОВЛЕНИЕ: Торрент '{}' ({}, ID: {}) обновлен на трекере.",
         torrent.name,
-        torrent.torrent_id
+        provider.name(),
+        topic_id
     );
     log::info!(
         "Старый хеш: {}. Новый хеш: {}",
         torrent.torrent_hash,
         new_hash
     );
+    if !changes.is_empty() {
+        log::info!("Изменения в теме: {}", changes.join("; "));
+    }
 
-    if dry_run {
-        return Ok(false);
+    if ctx.dry_run {
+        return Ok(UpdateOutcome::default());
     }
 
-    let topic_id: u64 = torrent
-        .torrent_id
-        .parse()
-        .with_context(|| format!("❌ Не удалось спарсить ID торрента '{}'", torrent.name))?;
+    // Файл удаляется при выходе из функции, в том числе при ошибке
+    let torrent_file = match metainfo {
+        Some(file) => file,
+        None => TempFile::new(provider.download_metainfo(topic_id).await?),
+    };
 
-    let torrent_file_path =
-        rutracker_api::download_torrent(client_for_download, headers, topic_id).await?;
+    let two_phase = &ctx.config.two_phase;
+    // В отдельную папку новая версия ничего не перезапишет
+    let to_preserve = if ctx.config.keep_previous.enabled && two_phase.new_save_path().is_none() {
+        files_to_preserve(ctx, torrent, torrent_file.path()).await?
+    } else {
+        Vec::new()
+    };
+
+    if ctx.config.free_space.enabled {
+        check_free_space(ctx, torrent, torrent_file.path(), &to_preserve).await?;
+    }
+
+    let preserved = preserve_previous(ctx, torrent, topic_id, new_hash, to_preserve).await?;
+    let mut outcome = UpdateOutcome {
+        preserved: preserved
+            .as_ref()
+            .map(|p| p.dir.to_string_lossy().into_owned()),
+        ..Default::default()
+    };
 
     if let Err(e) = add_torrent_from_file(
         client,
-        &torrent_file_path,
-        &torrent.save_path,
+        torrent_file.path(),
+        two_phase.new_save_path().unwrap_or(&torrent.save_path),
         &torrent.category,
         &torrent.tags,
+        two_phase.enabled && two_phase.add_paused,
     )
     .await
     {
         log::error!("❌ Не удалось добавить торрент из файла: {}", e);
-        let _ = fs::remove_file(&torrent_file_path).await;
+        undo_preserve(preserved).await;
         return Err(e);
     }
 
-    fs::remove_file(&torrent_file_path).await?;
+    if two_phase.enabled {
+        if let Err(e) = stage_update(ctx, provider, torrent, new_hash).await {
+            log::error!("❌ {:#}. Новый торрент убирается из клиента", e);
+            rollback_add(client, new_hash).await;
+            undo_preserve(preserved).await;
+            return Err(e);
+        }
+        return Ok(outcome);
+    }
+
+    // Старый .torrent сохраняем до конца проверки, чтобы было из чего восстановить раздачу
+    let recheck_config = &ctx.config.recheck;
+    let archive = if recheck_config.enabled {
+        let dir = Path::new(&recheck_config.archive_dir);
+        match recheck::archive_torrent(client, &torrent.torrent_hash, dir).await {
+            Ok(path) => Some(path),
+            Err(e) => {
+                log::warn!("⚠️ {:#}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
 
     let hashes_to_delete = vec![torrent.torrent_hash.clone()];
-    client.delete_torrents(hashes_to_delete, false).await?;
+    if let Err(e) = client.delete_torrents(hashes_to_delete, false).await {
+        // Откатываем добавление, чтобы в клиенте не остались обе версии раздачи
+        log::error!(
+            "❌ Не удалось удалить старый торрент, новый убирается из клиента: {}",
+            e
+        );
+        rollback_add(client, new_hash).await;
+        if let Some(path) = &archive {
+            let _ = fs::remove_file(path).await;
+        }
+        undo_preserve(preserved).await;
+        return Err(e.into());
+    }
 
-    Ok(true)
+    if recheck_config.enabled {
+        outcome.recheck = Some(RecheckWait {
+            entry: 0,
+            torrent: torrent.clone(),
+            torrent_ref: TorrentRef::new(provider.name(), torrent),
+            new_hash: new_hash.to_string(),
+            archive,
+        });
+    }
+    Ok(outcome)
+}
+
+/// Убирает из клиента только что добавленный торрент, когда обновление не удалось завершить
+async fn rollback_add(client: &Qbit, new_hash: &str) {
+    if let Err(e) = client
+        .delete_torrents(vec![new_hash.to_string()], false)
+        .await
+    {
+        log::error!(
+            "❌ Не удалось откатить добавление торрента {}: {}",
+            new_hash,
+            e
+        );
+    }
+}
+
+/// Возвращает на место файлы предыдущей версии, если обновление не состоялось
+async fn undo_preserve(preserved: Option<Preserved>) {
+    let Some(preserved) = preserved else {
+        return;
+    };
+    let dir = preserved.dir.clone();
+    let result = tokio::task::spawn_blocking(move || preserved.undo())
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|r| r);
+    match result {
+        Ok(()) => log::info!(
+            "↩️ Сохранение предыдущей версии отменено, файлы на месте ({})",
+            dir.display()
+        ),
+        Err(e) => log::error!(
+            "{:#}. Файлы предыдущей версии остались в {}",
+            e,
+            dir.display()
+        ),
+    }
+}
+
+/// Файлы старой версии на диске, которые перезапишет новая (`path` — ее .torrent)
+async fn files_to_preserve(
+    ctx: &ActionContext<'_>,
+    torrent: &Torrent,
+    path: &Path,
+) -> Result<Vec<MetainfoFile>> {
+    let data = fs::read(path)
+        .await
+        .with_context(|| format!("❌ Не удалось прочитать {}", path.display()))?;
+    let new = metainfo::parse_layout(&data)?;
+    // Нескачанных файлов на диске нет
+    let on_disk: Vec<MetainfoFile> = ctx
+        .client
+        .get_torrent_contents(&torrent.torrent_hash, None)
+        .await
+        .context("❌ Не удалось получить список файлов торрента")?
+        .into_iter()
+        .filter(|c| c.progress > 0.0)
+        .map(|c| MetainfoFile {
+            path: c.name,
+            size: c.size,
+        })
+        .collect();
+
+    // Хеши частей старой версии: по ним видно, какие файлы на самом деле изменятся
+    let old = match ctx.client.export_torrent(&torrent.torrent_hash).await {
+        Ok(data) => metainfo::parse_layout(&data).ok(),
+        Err(e) => {
+            log::warn!("⚠️ Не удалось выгрузить .torrent '{}': {}", torrent.name, e);
+            None
+        }
+    };
+    if !old.as_ref().is_some_and(|old| old.has_pieces()) {
+        log::warn!(
+            "⚠️ Хеши частей '{}' сравнить нельзя: сохраняются все файлы с тем же путем",
+            torrent.name
+        );
+    }
+
+    let files: Vec<MetainfoFile> = keep_previous::overwritten_files(&on_disk, old.as_ref(), &new)
+        .into_iter()
+        .cloned()
+        .collect();
+    if files.is_empty() {
+        log::debug!(
+            "В '{}' нет файлов, которые перезапишет новая версия",
+            torrent.name
+        );
+    }
+    Ok(files)
+}
+
+/// Сохраняет файлы старой версии, которые перезапишет новая (`[keep_previous]`).
+/// Возвращает сохраненную версию или `None`, если сохранять нечего.
+async fn preserve_previous(
+    ctx: &ActionContext<'_>,
+    torrent: &Torrent,
+    topic_id: TopicId,
+    new_hash: &str,
+    overwritten: Vec<MetainfoFile>,
+) -> Result<Option<Preserved>> {
+    let config = &ctx.config.keep_previous;
+    if overwritten.is_empty() {
+        return Ok(None);
+    }
+
+    let files: Vec<(std::path::PathBuf, String)> = overwritten
+        .iter()
+        .map(|f| {
+            (
+                config.local_path(&torrent.save_path, &f.path),
+                f.path.clone(),
+            )
+        })
+        .collect();
+    let version = PreservedVersion {
+        name: torrent.name.clone(),
+        hash: torrent.torrent_hash.clone(),
+        new_hash: new_hash.to_string(),
+        save_path: torrent.save_path.clone(),
+        files: files.iter().map(|(_, name)| name.clone()).collect(),
+    };
+    let topic_dir = Path::new(&config.dir).join(topic_id.to_string());
+    let mode = config.mode;
+    let count = files.len();
+
+    // Копирование больших файлов не должно останавливать остальные задачи
+    let preserved = tokio::task::spawn_blocking(move || {
+        keep_previous::preserve(&topic_dir, &files, &version, mode)
+    })
+    .await??;
+    log::info!(
+        "💾 Предыдущая версия '{}' (файлов: {}) сохранена в {}",
+        torrent.name,
+        count,
+        preserved.dir.display()
+    );
+    Ok(Some(preserved))
+}
+
+/// Первая фаза двухфазного обновления: старый торрент остается в клиенте (раздается или
+/// останавливается) и запоминается, чтобы удалить его, когда новая версия докачается
+async fn stage_update(
+    ctx: &ActionContext<'_>,
+    provider: &dyn TrackerProvider,
+    torrent: &Torrent,
+    new_hash: &str,
+) -> Result<()> {
+    let config = &ctx.config.two_phase;
+    // qBittorrent 5 не читает поле `paused` при добавлении, поэтому новую версию останавливаем
+    // отдельно: запущенная, она перезапишет файлы, которые еще раздает старая
+    if config.add_paused {
+        ctx.client
+            .stop_torrents(vec![new_hash.to_string()])
+            .await
+            .with_context(|| format!("❌ Не удалось остановить новую версию '{}'", torrent.name))?;
+    }
+
+    let mut old_paused = false;
+    if config.keep_old == KeepOld::Pause {
+        match ctx
+            .client
+            .stop_torrents(vec![torrent.torrent_hash.clone()])
+            .await
+        {
+            Ok(()) => old_paused = true,
+            Err(e) => log::warn!(
+                "⚠️ Не удалось остановить старый торрент '{}': {}",
+                torrent.name,
+                e
+            ),
+        }
+    }
+
+    let update = PendingUpdate {
+        tracker: provider.name().to_string(),
+        name: torrent.name.clone(),
+        topic_id: torrent.torrent_id,
+        new_hash: new_hash.to_lowercase(),
+        old_paused,
+        // Файлы общей папки нужны новой версии
+        delete_files: config.delete_old_files && config.save_path.is_some(),
+        added_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    };
+    let saved = {
+        let mut pending = ctx.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.insert(torrent.torrent_hash.clone(), update);
+        let saved = pending.save(Path::new(&config.state_path));
+        if saved.is_err() {
+            pending.remove(&torrent.torrent_hash);
+        }
+        saved
+    };
+    if let Err(e) = saved {
+        // Обновление откатывается, старый торрент снова раздается
+        if old_paused {
+            if let Err(e) = ctx
+                .client
+                .start_torrents(vec![torrent.torrent_hash.clone()])
+                .await
+            {
+                log::error!(
+                    "❌ Не удалось снова запустить торрент '{}': {}",
+                    torrent.name,
+                    e
+                );
+            }
+        }
+        return Err(e).context("❌ Незавершенное обновление не сохранено");
+    }
+
+    log::info!(
+        "⏳ Новая версия '{}' добавлена{}. Старый торрент {} и будет удален, когда она скачается на {:.0}%",
+        torrent.name,
+        if config.add_paused { " остановленной" } else { "" },
+        if old_paused { "остановлен" } else { "продолжает раздаваться" },
+        config.min_progress * 100.0
+    );
+    Ok(())
+}
+
+/// Хеши старых и новых торрентов незавершенных двухфазных обновлений
+fn pending_hashes(ctx: &ActionContext<'_>) -> HashSet<String> {
+    let pending = ctx.pending.lock().unwrap_or_else(|e| e.into_inner());
+    pending
+        .iter()
+        .flat_map(|(old, update)| [old.clone(), update.new_hash.clone()])
+        .collect()
+}
+
+/// Вторая фаза двухфазного обновления: удаляет старые торренты, чьи новые версии докачались.
+/// Ошибки не прерывают запуск: обновление повторится в следующий раз.
+async fn finish_pending_updates(ctx: &ActionContext<'_>, report: &mut RunReport) {
+    let ActionContext {
+        client,
+        config,
+        dry_run,
+        events,
+        ..
+    } = *ctx;
+    let updates: Vec<(String, PendingUpdate)> = {
+        let pending = ctx.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending
+            .iter()
+            .map(|(hash, update)| (hash.clone(), update.clone()))
+            .collect()
+    };
+    if updates.is_empty() {
+        return;
+    }
+
+    let hashes: Vec<&str> = updates
+        .iter()
+        .flat_map(|(old, update)| [old.as_str(), update.new_hash.as_str()])
+        .collect();
+    let list = match client
+        .get_torrent_list(GetTorrentListArg {
+            hashes: Some(hashes.join("|")),
+            ..Default::default()
+        })
+        .await
+    {
+        Ok(list) => list,
+        Err(e) => {
+            log::warn!("⚠️ Не удалось проверить незавершенные обновления: {}", e);
+            return;
+        }
+    };
+    let by_hash: HashMap<String, &qbit_rs::model::Torrent> = list
+        .iter()
+        .filter_map(|t| t.hash.as_ref().map(|h| (h.to_lowercase(), t)))
+        .collect();
+
+    let mut finished = Vec::new();
+    for (old_hash, update) in &updates {
+        if ctx.shutdown.is_requested() {
+            break;
+        }
+        let step = pending::next_step(
+            by_hash.contains_key(old_hash),
+            by_hash.get(&update.new_hash).copied(),
+            config.two_phase.min_progress,
+        );
+        match step {
+            Step::Wait(progress) => log::info!(
+                hash = old_hash.as_str(), new_hash = update.new_hash.as_str();
+                "⏳ Новая версия '{}' скачана на {:.1}%, старый торрент пока остается",
+                update.name,
+                progress * 100.0
+            ),
+            Step::OldMissing => {
+                log::info!(
+                    "Старого торрента '{}' уже нет в клиенте, обновление завершено",
+                    update.name
+                );
+                finished.push(old_hash);
+            }
+            Step::NewMissing => {
+                log::warn!(
+                    hash = old_hash.as_str(), new_hash = update.new_hash.as_str();
+                    "⚠️ Новой версии '{}' нет в клиенте, двухфазное обновление отменено",
+                    update.name
+                );
+                if update.old_paused && !dry_run {
+                    if let Err(e) = client.start_torrents(vec![old_hash.clone()]).await {
+                        log::error!(
+                            "❌ Не удалось снова запустить торрент '{}': {}",
+                            update.name,
+                            e
+                        );
+                    }
+                }
+                finished.push(old_hash);
+            }
+            Step::Finish => {
+                log::warn!(
+                    hash = old_hash.as_str(), new_hash = update.new_hash.as_str(), action = "update";
+                    "{}✅ Новая версия '{}' готова, старый торрент удаляется{}",
+                    if dry_run { "(Dry Run) " } else { "" },
+                    update.name,
+                    if update.delete_files { " вместе с файлами" } else { "" }
+                );
+                let mut entry = ReportEntry::pending(old_hash, update, ReportAction::Updated);
+                if !dry_run {
+                    match client
+                        .delete_torrents(vec![old_hash.clone()], update.delete_files)
+                        .await
+                    {
+                        Ok(()) => {
+                            finished.push(old_hash);
+                            events
+                                .emit(UpdaterEvent::UpdateFinished {
+                                    torrent: TorrentRef {
+                                        tracker: update.tracker.clone(),
+                                        name: update.name.clone(),
+                                        hash: old_hash.clone(),
+                                        topic_id: update.topic_id,
+                                    },
+                                    new_hash: update.new_hash.clone(),
+                                })
+                                .await;
+                        }
+                        Err(e) => {
+                            log::error!(
+                                "❌ Не удалось удалить старый торрент '{}': {}",
+                                update.name,
+                                e
+                            );
+                            entry.action = ReportAction::Failed;
+                            entry.error = Some(e.to_string());
+                        }
+                    }
+                }
+                report.push(entry);
+            }
+        }
+    }
+
+    if finished.is_empty() {
+        return;
+    }
+    let mut pending = ctx.pending.lock().unwrap_or_else(|e| e.into_inner());
+    for hash in finished {
+        pending.remove(hash);
+    }
+    if !dry_run {
+        if let Err(e) = pending.save(Path::new(&config.two_phase.state_path)) {
+            log::warn!("⚠️ {:#}", e);
+        }
+    }
+}
+
+/// Проверяет, хватит ли места на докачку новой версии. Ошибка `LowSpace` означает,
+/// что обновление нужно отложить.
+async fn check_free_space(
+    ctx: &ActionContext<'_>,
+    torrent: &Torrent,
+    path: &Path,
+    to_preserve: &[MetainfoFile],
+) -> Result<()> {
+    let data = tokio::fs::read(path)
+        .await
+        .with_context(|| format!("❌ Не удалось прочитать {}", path.display()))?;
+    let new = metainfo::parse_files(&data)?;
+    // В отдельную папку новая версия качается целиком
+    let present: Vec<PresentFile> = if ctx.config.two_phase.new_save_path().is_some() {
+        Vec::new()
+    } else {
+        ctx.client
+            .get_torrent_contents(&torrent.torrent_hash, None)
+            .await
+            .context("❌ Не удалось получить список файлов торрента")?
+            .into_iter()
+            .map(|c| PresentFile {
+                path: c.name,
+                size: c.size,
+                progress: c.progress,
+            })
+            .collect()
+    };
+
+    let keep = &ctx.config.keep_previous;
+    let mut present = present;
+    // Перенесенные в папку версий файлы новая версия скачает заново
+    if keep.mode == PreserveMode::Move {
+        present.retain(|f| !to_preserve.iter().any(|p| p.path == f.path));
+    }
+    let mut needed = disk_space::missing_bytes(&present, &new);
+    // Копии файлов предыдущей версии занимают место на том же диске
+    if keep.mode == PreserveMode::Copy
+        && !to_preserve.is_empty()
+        && disk_space::same_disk(
+            &keep.local_path(&torrent.save_path, ""),
+            Path::new(&keep.dir),
+        )
+    {
+        needed += to_preserve.iter().map(|f| f.size).sum::<u64>();
+    }
+    log::debug!(
+        "Для обновления '{}' нужно докачать {} байт",
+        torrent.name,
+        needed
+    );
+    ctx.disk.reserve(ctx.client, needed).await
+}
+
+/// Проверка данных обновленного торрента, отложенная до конца запуска
+struct RecheckWait {
+    /// Номер записи об обновлении в отчете
+    entry: usize,
+    /// Старая версия торрента
+    torrent: Torrent,
+    torrent_ref: TorrentRef,
+    new_hash: String,
+    /// Сохраненный .torrent старой версии
+    archive: Option<std::path::PathBuf>,
+}
+
+/// Дожидается проверки данных всех обновленных в этом запуске торрентов: проверки идут
+/// в qBittorrent одновременно, поэтому ждем их вместе, а не по очереди
+async fn wait_rechecks(ctx: &ActionContext<'_>, report: &mut RunReport) {
+    let waits = std::mem::take(&mut *ctx.rechecks.lock().unwrap_or_else(|e| e.into_inner()));
+    if waits.is_empty() {
+        return;
+    }
+    log::info!(
+        "🔍 Ожидание проверки данных обновленных торрентов: {}",
+        waits.len()
+    );
+
+    let results = futures::future::join_all(
+        waits
+            .iter()
+            .map(|w| verify_recheck(ctx, &w.torrent, &w.new_hash, w.archive.clone())),
+    )
+    .await;
+
+    for (wait, result) in waits.into_iter().zip(results) {
+        let Some(entry) = report.entries.get_mut(wait.entry) else {
+            continue;
+        };
+        match result {
+            Ok(recheck) => {
+                entry.recheck = Some(recheck.clone());
+                ctx.events
+                    .emit(UpdaterEvent::RecheckFinished {
+                        torrent: wait.torrent_ref,
+                        recheck,
+                    })
+                    .await;
+            }
+            Err(e) => {
+                log::error!(
+                    "❌ Не удалось дождаться проверки данных '{}': {:#}",
+                    wait.torrent.name,
+                    e
+                );
+                entry.error = Some(format!("{:#}", e));
+                ctx.events
+                    .emit(UpdaterEvent::Error {
+                        torrent: Some(wait.torrent_ref),
+                        action: Some(RuleAction::Update.to_string()),
+                        message: format!("{:#}", e),
+                    })
+                    .await;
+            }
+        }
+    }
+}
+
+/// Дожидается проверки данных нового торрента и сравнивает найденный объем с ожидаемым
+async fn verify_recheck(
+    ctx: &ActionContext<'_>,
+    torrent: &Torrent,
+    new_hash: &str,
+    archive: Option<std::path::PathBuf>,
+) -> Result<Recheck> {
+    let config = &ctx.config.recheck;
+    log::info!(
+        "🔍 Ожидание проверки данных нового торрента '{}'...",
+        torrent.name
+    );
+
+    let Some((progress, size)) =
+        recheck::wait_recheck(ctx.client, new_hash, config, ctx.shutdown).await?
+    else {
+        log::warn!(
+            "⚠️ Проверка данных '{}' не завершилась за {} с; старый .torrent сохранен: {}",
+            torrent.name,
+            config.timeout_secs,
+            archive
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default()
+        );
+        return Ok(Recheck {
+            finished: false,
+            progress: 0.0,
+            present_bytes: 0,
+            size: 0,
+            expected_bytes: torrent.size,
+            low: false,
+            paused: false,
+            archive: archive.map(|p| p.to_string_lossy().into_owned()),
+        });
+    };
+
+    let mut result = Recheck::evaluate(progress, size, torrent.size, config.min_ratio);
+    log::info!(
+        hash = new_hash, progress = result.progress;
+        "🔍 Проверка '{}' завершена: на диске {} из {} MB ({:.1}%)",
+        torrent.name,
+        result.present_bytes / (1024 * 1024),
+        result.size / (1024 * 1024),
+        result.progress * 100.0
+    );
+
+    if !result.low {
+        if let Some(path) = &archive {
+            let _ = fs::remove_file(path).await;
+        }
+        return Ok(result);
+    }
+
+    log::warn!(
+        hash = new_hash;
+        "⚠️ У '{}' найдено только {} MB из ожидаемых {} MB. Проверьте папку сохранения.",
+        torrent.name,
+        result.present_bytes / (1024 * 1024),
+        result.expected_bytes / (1024 * 1024)
+    );
+    if config.pause_on_low {
+        match ctx.client.stop_torrents(vec![new_hash.to_string()]).await {
+            Ok(()) => {
+                result.paused = true;
+                log::warn!("⏸️ Торрент '{}' остановлен до проверки", torrent.name);
+            }
+            Err(e) => log::error!("❌ Не удалось остановить торрент '{}': {}", torrent.name, e),
+        }
+    }
+    if let Some(path) = &archive {
+        log::warn!(
+            "💾 .torrent старой версии сохранен для восстановления: {}",
+            path.display()
+        );
+    }
+    result.archive = archive.map(|p| p.to_string_lossy().into_owned());
+    Ok(result)
 }
 
 async fn handle_deletion(client: &Qbit, torrent: &Torrent, dry_run: bool) -> Result<bool> {
     log::warn!(
+        topic_id:% = torrent.topic_id_str(), hash = torrent.torrent_hash.as_str(), action = "delete";
         "❌ УДАЛЕН: Торрент '{}' (ID: {}) удален с трекера.",
         torrent.name,
-        torrent.torrent_id
+        torrent.topic_id_str()
     );
 
     if dry_run {
@@ -348,32 +1986,76 @@ async fn handle_deletion(client: &Qbit, torrent: &Torrent, dry_run: bool) -> Res
     Ok(true)
 }
 
+async fn handle_pause(client: &Qbit, torrent: &Torrent, dry_run: bool) -> Result<()> {
+    log::warn!(
+        topic_id:% = torrent.topic_id_str(), hash = torrent.torrent_hash.as_str(), action = "pause";
+        "⏸️ ОСТАНОВКА: Торрент '{}' (ID: {}).",
+        torrent.name,
+        torrent.topic_id_str()
+    );
+
+    if dry_run {
+        return Ok(());
+    }
+
+    client
+        .stop_torrents(vec![torrent.torrent_hash.clone()])
+        .await?;
+    Ok(())
+}
+
+async fn handle_tag(client: &Qbit, torrent: &Torrent, tag: &str, dry_run: bool) -> Result<()> {
+    log::info!(
+        topic_id:% = torrent.topic_id_str(), hash = torrent.torrent_hash.as_str(), action = "tag", tag;
+        "🏷️ ТЕГ: Торрент '{}' (ID: {}) помечается тегом '{}'.",
+        torrent.name,
+        torrent.topic_id_str(),
+        tag
+    );
+
+    if dry_run {
+        return Ok(());
+    }
+
+    client
+        .add_torrent_tags(vec![torrent.torrent_hash.clone()], vec![tag.to_string()])
+        .await?;
+    Ok(())
+}
+
 async fn add_torrent_from_file(
     client: &Qbit,
-    file_path: &str,
+    file_path: &Path,
     save_path: &str,
     category: &str,
     tags: &str,
+    paused: bool,
 ) -> Result<()> {
-    let torrent_content = fs::read(file_path)
-        .await
-        .with_context(|| format!("❌ Ошибка: Не удалось прочитать файл '{}'", file_path))?;
+    let torrent_content = fs::read(file_path).await.with_context(|| {
+        format!(
+            "❌ Ошибка: Не удалось прочитать файл '{}'",
+            file_path.display()
+        )
+    })?;
 
     let torrent_file = TorrentFile {
         data: torrent_content,
-        filename: file_path.to_string(),
+        filename: file_path.to_string_lossy().into_owned(),
     };
 
     let torrent_source = TorrentSource::TorrentFiles {
         torrents: vec![torrent_file],
     };
 
-    let arg = AddTorrentArg::builder()
+    let mut arg = AddTorrentArg::builder()
         .source(torrent_source)
         .savepath(save_path.to_string())
         .tags(tags.to_string())
         .category(category.to_string())
         .build();
+    if paused {
+        arg.paused = Some("true".to_string());
+    }
 
     client
         .add_torrent(arg)
@@ -0,0 +1,179 @@
+// src/lock.rs
+
+//!
+//! Блокировка от одновременного запуска: два экземпляра, работающие с одним qBittorrent,
+//! скачали бы и добавили один и тот же новый торрент и дважды удалили бы старый
+//!
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Кто держит блокировку (содержимое файла блокировки)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LockHolder {
+    pub pid: u32,
+    /// Время захвата (unix time)
+    pub started_at: u64,
+    /// Адрес qBittorrent, для которого взята блокировка
+    pub qbit_url: String,
+}
+
+/// Блокировка экземпляра; снимается при удалении значения или завершении процесса
+#[derive(Debug)]
+pub struct InstanceLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Берет блокировку для экземпляра qBittorrent по адресу `qbit_url`
+    pub fn acquire(qbit_url: &str) -> Result<Self> {
+        Self::acquire_at(&lock_path(qbit_url), qbit_url)
+    }
+
+    /// Берет блокировку в файле `path`.
+    ///
+    /// Блокировка файла снимается системой при завершении процесса, поэтому файл
+    /// от аварийно завершившегося запуска не мешает: он просто перезаписывается.
+    pub fn acquire_at(path: &Path, qbit_url: &str) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("❌ Не удалось создать папку {}", dir.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .with_context(|| format!("❌ Не удалось открыть файл блокировки {}", path.display()))?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let holder = match read_holder(&mut file) {
+                    Some(h) => format!("PID {}, запущен {} (unix time)", h.pid, h.started_at),
+                    None => "владелец неизвестен".to_string(),
+                };
+                anyhow::bail!(
+                    "❌ Для {} уже работает другой экземпляр tracker-updater ({}). \
+                     Дождитесь его завершения. Файл блокировки: {}",
+                    qbit_url,
+                    holder,
+                    path.display()
+                );
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| {
+                    format!("❌ Не удалось заблокировать файл {}", path.display())
+                })
+            }
+        }
+
+        if let Some(stale) = read_holder(&mut file) {
+            log::warn!(
+                "⚠️ Предыдущий запуск (PID {}) завершился, не сняв блокировку; она перехвачена",
+                stale.pid
+            );
+        }
+
+        let holder = LockHolder {
+            pid: std::process::id(),
+            started_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            qbit_url: qbit_url.to_string(),
+        };
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&serde_json::to_vec(&holder)?)?;
+        file.flush()?;
+        log::debug!("Блокировка {} взята", path.display());
+
+        Ok(InstanceLock {
+            file,
+            path: path.to_path_buf(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // Файл не удаляем: иначе другой процесс мог бы заблокировать уже удаленный файл.
+        // Пустой файл означает, что блокировка снята штатно.
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+fn read_holder(file: &mut File) -> Option<LockHolder> {
+    let mut data = Vec::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_end(&mut data).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+/// Файл блокировки для экземпляра qBittorrent: `<temp>/tracker-updater/locks/<адрес>.lock`
+pub fn lock_path(qbit_url: &str) -> PathBuf {
+    let name: String = qbit_url
+        .trim_end_matches('/')
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    crate::paths::download_dir()
+        .join("locks")
+        .join(format!("{}.lock", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_path() {
+        assert!(lock_path("http://localhost:8080/").ends_with("locks/http___localhost_8080.lock"));
+    }
+
+    #[test]
+    fn test_second_lock_is_refused() {
+        let path = std::env::temp_dir().join("tracker-updater-lock-test.lock");
+        let _ = std::fs::remove_file(&path);
+
+        let lock = InstanceLock::acquire_at(&path, "http://qbit").unwrap();
+        let error = InstanceLock::acquire_at(&path, "http://qbit")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(&format!("PID {}", std::process::id())));
+
+        drop(lock);
+        let lock = InstanceLock::acquire_at(&path, "http://qbit").unwrap();
+        drop(lock);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_stale_lock_is_taken_over() {
+        let path = std::env::temp_dir().join("tracker-updater-stale-lock-test.lock");
+        let stale = LockHolder {
+            pid: u32::MAX,
+            started_at: 1,
+            qbit_url: "http://qbit".into(),
+        };
+        std::fs::write(&path, serde_json::to_vec(&stale).unwrap()).unwrap();
+
+        let mut lock = InstanceLock::acquire_at(&path, "http://qbit").unwrap();
+        let holder = read_holder(&mut lock.file).unwrap();
+        assert_eq!(holder.pid, std::process::id());
+        drop(lock);
+        let _ = std::fs::remove_file(&path);
+    }
+}
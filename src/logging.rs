@@ -0,0 +1,198 @@
+// src/logging.rs
+
+//!
+//! Инициализация логгера: `log4rs.yaml`, если он есть, иначе встроенная конфигурация
+//! с настройками из командной строки (уровень, файл, тихий режим, JSON Lines)
+//!
+
+use anyhow::{Context, Result};
+use log::LevelFilter;
+use log4rs::append::console::{ConsoleAppender, Target};
+use log4rs::append::rolling_file::policy::compound::roll::fixed_window::FixedWindowRoller;
+use log4rs::append::rolling_file::policy::compound::trigger::size::SizeTrigger;
+use log4rs::append::rolling_file::policy::compound::CompoundPolicy;
+use log4rs::append::rolling_file::RollingFileAppender;
+use log4rs::config::{Appender, Root};
+use log4rs::encode::json::JsonEncoder;
+use log4rs::encode::pattern::PatternEncoder;
+use log4rs::encode::Encode;
+use log4rs::filter::threshold::ThresholdFilter;
+use std::path::{Path, PathBuf};
+
+/// Имя файла конфигурации log4rs, который используется, если логирование не настроено флагами
+pub const LOG4RS_CONFIG: &str = "log4rs.yaml";
+
+/// Размер файла лога, после которого он ротируется
+const LOG_FILE_LIMIT: u64 = 10 * 1024 * 1024;
+/// Сколько старых файлов лога хранить
+const LOG_FILE_COUNT: u32 = 5;
+
+/// Формат записей лога
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Текст для человека
+    #[default]
+    Text,
+    /// JSON Lines: одна запись на строку, со структурными полями в `attributes`
+    Json,
+}
+
+/// Настройки логирования из командной строки
+#[derive(Debug, Clone, Default)]
+pub struct LogOptions {
+    /// Уровень логов (по умолчанию info в консоли и debug в файле)
+    pub level: Option<LevelFilter>,
+    /// Файл лога с ротацией
+    pub file: Option<PathBuf>,
+    /// В консоль выводить только предупреждения и ошибки
+    pub quiet: bool,
+    pub format: LogFormat,
+}
+
+impl LogOptions {
+    /// Заданы ли настройки, ради которых `log4rs.yaml` игнорируется
+    fn overrides_file(&self) -> bool {
+        self.level.is_some() || self.file.is_some() || self.quiet || self.format != LogFormat::Text
+    }
+}
+
+/// Инициализирует логгер.
+///
+/// Файл `yaml` (log4rs) используется, только если он есть и логирование не настроено флагами.
+/// Если файл некорректен, включается встроенная конфигурация и в лог пишется предупреждение.
+pub fn init(options: &LogOptions, yaml: &Path) -> Result<()> {
+    let mut fallback_reason = None;
+
+    if !options.overrides_file() && yaml.exists() {
+        match log4rs::init_file(yaml, Default::default()) {
+            Ok(()) => {
+                log::debug!("{} загружен, логгер инициализирован.", yaml.display());
+                return Ok(());
+            }
+            Err(e) => fallback_reason = Some(e.to_string()),
+        }
+    }
+
+    log4rs::init_config(build_config(options)?).context("❌ Не удалось инициализировать логгер")?;
+
+    if let Some(reason) = fallback_reason {
+        log::warn!(
+            "⚠️ Ошибка в {}, используются настройки логирования по умолчанию: {}",
+            yaml.display(),
+            reason
+        );
+    }
+    Ok(())
+}
+
+/// Встроенная конфигурация: консоль и, если задан, файл с ротацией
+pub fn build_config(options: &LogOptions) -> Result<log4rs::Config> {
+    let console_level = if options.quiet {
+        LevelFilter::Warn
+    } else {
+        options.level.unwrap_or(LevelFilter::Info)
+    };
+
+    let console = ConsoleAppender::builder()
+        .target(Target::Stdout)
+        .encoder(encoder(options.format, "{m}{n}"))
+        .build();
+
+    let mut builder = log4rs::Config::builder().appender(
+        Appender::builder()
+            .filter(Box::new(ThresholdFilter::new(console_level)))
+            .build("console", Box::new(console)),
+    );
+    let mut root = Root::builder().appender("console");
+    let mut root_level = console_level;
+
+    if let Some(path) = &options.file {
+        let file_level = options.level.unwrap_or(LevelFilter::Debug);
+        let roller = FixedWindowRoller::builder()
+            .base(1)
+            .build(&rolled_pattern(path), LOG_FILE_COUNT)
+            .context("❌ Некорректный путь к файлу лога")?;
+        let policy =
+            CompoundPolicy::new(Box::new(SizeTrigger::new(LOG_FILE_LIMIT)), Box::new(roller));
+        let file = RollingFileAppender::builder()
+            .encoder(encoder(
+                options.format,
+                "{d(%Y-%m-%d %H:%M:%S)} [{l}] - {m}{n}",
+            ))
+            .build(path, Box::new(policy))
+            .with_context(|| format!("❌ Не удалось открыть файл лога {}", path.display()))?;
+
+        builder = builder.appender(
+            Appender::builder()
+                .filter(Box::new(ThresholdFilter::new(file_level)))
+                .build("file", Box::new(file)),
+        );
+        root = root.appender("file");
+        root_level = root_level.max(file_level);
+    }
+
+    builder
+        .build(root.build(root_level))
+        .context("❌ Некорректная конфигурация логгера")
+}
+
+fn encoder(format: LogFormat, pattern: &str) -> Box<dyn Encode> {
+    match format {
+        LogFormat::Text => Box::new(PatternEncoder::new(pattern)),
+        LogFormat::Json => Box::new(JsonEncoder::new()),
+    }
+}
+
+/// Шаблон имен старых файлов: `logs/app.log` → `logs/app.{}.log`
+fn rolled_pattern(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{}.{{}}.{}", stem, ext.to_string_lossy()),
+        None => format!("{}.{{}}", stem),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolled_pattern() {
+        assert_eq!(
+            rolled_pattern(Path::new("logs/my_app.log")),
+            "logs/my_app.{}.log"
+        );
+        assert_eq!(rolled_pattern(Path::new("updater")), "updater.{}");
+    }
+
+    #[test]
+    fn test_overrides_file() {
+        assert!(!LogOptions::default().overrides_file());
+        assert!(LogOptions {
+            quiet: true,
+            ..Default::default()
+        }
+        .overrides_file());
+        assert!(LogOptions {
+            format: LogFormat::Json,
+            ..Default::default()
+        }
+        .overrides_file());
+    }
+
+    #[test]
+    fn test_build_console_config() {
+        let config = build_config(&LogOptions {
+            level: Some(LevelFilter::Trace),
+            format: LogFormat::Json,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(config.root().level(), LevelFilter::Trace);
+        assert_eq!(config.appenders().len(), 1);
+    }
+}
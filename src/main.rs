@@ -1,35 +1,126 @@
 // src/main.rs
 
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use config::{Config as ConfigBuilder, File};
-use tracker_updater::{run_helper, Config};
+use log::LevelFilter;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracker_updater::logging::{self, LogFormat, LogOptions, LOG4RS_CONFIG};
+use tracker_updater::paths::ConfigLookup;
+use tracker_updater::{apply_helper, daemon_helper, plan_helper, run_helper, Config};
 
 #[derive(Parser, Debug)]
 #[command(version, about)]
 struct Args {
-    /// Путь к файлу конфигурации
-    #[arg(short, long, default_value = "config.toml")]
-    config: String,
+    /// Путь к файлу конфигурации. Если не указан: переменная TRACKER_UPDATER_CONFIG,
+    /// затем config.toml рядом с программой, в XDG-каталогах и в текущей папке
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// Подтверждать каждое обновление и удаление в терминале
+    #[arg(short, long)]
+    interactive: bool,
+
+    /// Уровень логов: error, warn, info, debug, trace (отменяет log4rs.yaml)
+    #[arg(long, global = true)]
+    log_level: Option<LevelFilter>,
+
+    /// Писать лог в файл с ротацией (отменяет log4rs.yaml)
+    #[arg(long, global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Выводить в консоль только предупреждения и ошибки (отменяет log4rs.yaml)
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Формат лога: text или json (JSON Lines для сборщиков логов)
+    #[arg(long, value_enum, default_value_t = LogFormatArg::Text, global = true)]
+    log_format: LogFormatArg,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum LogFormatArg {
+    Text,
+    Json,
+}
+
+impl Args {
+    fn log_options(&self) -> LogOptions {
+        LogOptions {
+            level: self.log_level,
+            file: self.log_file.clone(),
+            quiet: self.quiet,
+            format: match self.log_format {
+                LogFormatArg::Text => LogFormat::Text,
+                LogFormatArg::Json => LogFormat::Json,
+            },
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Проверить торренты и сразу выполнить действия (по умолчанию)
+    Run,
+    /// Проверить торренты и сохранить план действий в файл, ничего не меняя
+    Plan {
+        /// Файл плана
+        #[arg(short, long, default_value = "plan.json")]
+        output: PathBuf,
+    },
+    /// Выполнить сохраненный план, отклоняя действия, для которых что-то изменилось
+    Apply {
+        /// Файл плана
+        plan: PathBuf,
+    },
+    /// Работать в фоне, проверяя торренты с заданным интервалом
+    Daemon {
+        /// Интервал между проверками, минуты
+        #[arg(short = 'n', long, default_value_t = 60)]
+        interval: u64,
+    },
 }
 
 /// Главная асинхронная функция, обрабатывающая ошибки
 async fn run() -> Result<()> {
     let args = Args::parse();
 
-    // 1. Инициализация логгера
-    tracker_updater::init_logger();
+    // 1. Поиск файла конфигурации и инициализация логгера.
+    // log4rs.yaml ищется рядом с конфигурацией, затем в текущей папке.
+    let config_path = ConfigLookup::from_env(args.config.clone()).find();
+    let config_dir = config_path
+        .as_ref()
+        .ok()
+        .and_then(|p| p.parent())
+        .map(Path::to_path_buf);
+    let yaml = config_dir
+        .as_ref()
+        .map(|dir| dir.join(LOG4RS_CONFIG))
+        .filter(|p| p.is_file())
+        .unwrap_or_else(|| PathBuf::from(LOG4RS_CONFIG));
+
+    if let Err(e) = logging::init(&args.log_options(), &yaml) {
+        // Логгера нет, поэтому пишем прямо в консоль
+        eprintln!("{:#}", e);
+        std::process::exit(1);
+    }
 
-    log::info!("⚙️ Загрузка конфигурации из {}...", args.config);
+    let config_path = config_path?;
+    log::info!("⚙️ Загрузка конфигурации из {}...", config_path.display());
 
     // 2. Сборка конфигурации из файла
-    let config: Config = ConfigBuilder::builder()
-        .add_source(File::with_name(&args.config).required(true))
+    let mut config: Config = ConfigBuilder::builder()
+        .add_source(File::from(config_path.as_path()).required(true))
         .build()
         .with_context(|| {
             format!(
                 "❌ Ошибка загрузки файла {}. Убедитесь, что файл существует.",
-                args.config
+                config_path.display()
             )
         })?
         .try_deserialize::<Config>()
@@ -37,19 +128,52 @@ async fn run() -> Result<()> {
             "❌ Ошибка парсинга конфигурации. Убедитесь, что файл имеет правильную структуру.",
         )?;
 
+    // Относительные пути в конфигурации считаются от папки файла конфигурации
+    if let Some(dir) = &config_dir {
+        config.resolve_paths(dir);
+    }
+    config.validate()?;
+
     log::debug!(
         "Конфигурация загружена: dry_run = {}, qbit.url = {}",
         config.dry_run,
         config.qbit.url
     );
 
+    if args.interactive {
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!("❌ Интерактивный режим требует запуска в терминале");
+        }
+        config.interactive = true;
+    }
+
     if config.dry_run {
         log::warn!("--- 🟢 Включен режим пробного запуска (Dry Run) ---");
         log::warn!("--- 🟢 Никакие торренты не будут изменены или удалены ---");
     }
 
     // 3. Запуск основного процесса
-    run_helper(config).await?;
+    match args.command.unwrap_or(Command::Run) {
+        Command::Run => run_helper(config).await?,
+        Command::Plan { output } => {
+            if config.interactive {
+                anyhow::bail!("❌ Интерактивный режим недоступен при составлении плана");
+            }
+            plan_helper(config, &output).await?
+        }
+        Command::Apply { plan } => {
+            if config.interactive {
+                anyhow::bail!("❌ Интерактивный режим недоступен при выполнении плана");
+            }
+            apply_helper(config, &plan).await?
+        }
+        Command::Daemon { interval } => {
+            if config.interactive {
+                anyhow::bail!("❌ Интерактивный режим недоступен в фоновом режиме");
+            }
+            daemon_helper(config, Duration::from_secs(interval.max(1) * 60)).await?
+        }
+    }
 
     log::info!("✅ Работа успешно завершена.");
     Ok(())
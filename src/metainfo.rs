@@ -0,0 +1,210 @@
+// src/metainfo.rs
+
+//!
+//! Минимальный разбор .torrent файла: список файлов раздачи и сравнение со списком в qBittorrent
+//!
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_bencode::value::Value;
+use std::collections::BTreeMap;
+
+#[derive(Deserialize)]
+struct Metainfo {
+    info: Info,
+}
+
+#[derive(Deserialize)]
+struct Info {
+    name: String,
+    /// Размер для раздачи из одного файла
+    length: Option<u64>,
+    /// Файлы многофайловой раздачи
+    files: Option<Vec<InfoFile>>,
+    #[serde(rename = "piece length", default)]
+    piece_length: u64,
+    /// SHA-1 частей подряд (байтовая строка)
+    pieces: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct InfoFile {
+    length: u64,
+    path: Vec<String>,
+}
+
+/// Файл раздачи: путь как в qBittorrent (`папка/подпапка/файл`) и размер в байтах
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetainfoFile {
+    pub path: String,
+    pub size: u64,
+}
+
+/// Размер SHA-1 одной части
+const PIECE_HASH_LEN: usize = 20;
+
+/// Файлы раздачи по порядку и хеши ее частей: по ним видно, какие файлы изменились
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    pub files: Vec<MetainfoFile>,
+    /// Размер части, байты (0 — неизвестен)
+    pub piece_length: u64,
+    /// SHA-1 частей подряд
+    pieces: Vec<u8>,
+}
+
+impl Layout {
+    /// Хеш части с номером `index`
+    pub fn piece(&self, index: u64) -> Option<&[u8]> {
+        let start = usize::try_from(index).ok()?.checked_mul(PIECE_HASH_LEN)?;
+        self.pieces.get(start..start + PIECE_HASH_LEN)
+    }
+
+    /// Есть ли хеши частей, по которым можно сравнивать содержимое
+    pub fn has_pieces(&self) -> bool {
+        self.piece_length > 0 && !self.pieces.is_empty()
+    }
+
+    /// Смещение каждого файла от начала раздачи
+    pub fn offsets(&self) -> impl Iterator<Item = (&MetainfoFile, u64)> {
+        self.files.iter().scan(0u64, |offset, file| {
+            let start = *offset;
+            *offset += file.size;
+            Some((file, start))
+        })
+    }
+}
+
+/// Читает список файлов из содержимого .torrent файла
+pub fn parse_files(data: &[u8]) -> Result<Vec<MetainfoFile>> {
+    parse_layout(data).map(|layout| layout.files)
+}
+
+/// Читает файлы и хеши частей из содержимого .torrent файла
+pub fn parse_layout(data: &[u8]) -> Result<Layout> {
+    let metainfo: Metainfo =
+        serde_bencode::from_bytes(data).context("Не удалось разобрать .torrent файл")?;
+    let info = metainfo.info;
+    let pieces = match info.pieces {
+        Some(Value::Bytes(pieces)) if pieces.len() % PIECE_HASH_LEN == 0 => pieces,
+        _ => Vec::new(),
+    };
+
+    let files = match (info.files, info.length) {
+        (Some(files), _) => files
+            .into_iter()
+            .map(|f| MetainfoFile {
+                path: std::iter::once(info.name.as_str())
+                    .chain(f.path.iter().map(String::as_str))
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                size: f.length,
+            })
+            .collect(),
+        (None, Some(length)) => vec![MetainfoFile {
+            path: info.name,
+            size: length,
+        }],
+        (None, None) => anyhow::bail!("В .torrent файле нет списка файлов"),
+    };
+    Ok(Layout {
+        files,
+        piece_length: info.piece_length,
+        pieces,
+    })
+}
+
+/// Построчное описание отличий: `+` новый файл, `-` удаленный, `~` изменился размер
+pub fn diff_files(old: &[MetainfoFile], new: &[MetainfoFile]) -> Vec<String> {
+    let old: BTreeMap<&str, u64> = old.iter().map(|f| (f.path.as_str(), f.size)).collect();
+    let new: BTreeMap<&str, u64> = new.iter().map(|f| (f.path.as_str(), f.size)).collect();
+    let mut lines = Vec::new();
+
+    for (path, size) in &old {
+        match new.get(path) {
+            None => lines.push(format!("- {} ({} байт)", path, size)),
+            Some(new_size) if new_size != size => {
+                lines.push(format!("~ {} ({} → {} байт)", path, size, new_size))
+            }
+            Some(_) => {}
+        }
+    }
+    for (path, size) in &new {
+        if !old.contains_key(path) {
+            lines.push(format!("+ {} ({} байт)", path, size));
+        }
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, size: u64) -> MetainfoFile {
+        MetainfoFile {
+            path: path.into(),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_parse_multi_file() {
+        let data = b"d4:infod5:filesld6:lengthi10e4:pathl3:cd15:a.mp3eed6:lengthi20e4:pathl5:b.txteee4:name5:Album12:piece lengthi16384e6:pieces0:ee";
+        assert_eq!(
+            parse_files(data).unwrap(),
+            vec![file("Album/cd1/a.mp3", 10), file("Album/b.txt", 20)]
+        );
+    }
+
+    #[test]
+    fn test_parse_single_file() {
+        let data = b"d4:infod6:lengthi42e4:name8:disk.iso12:piece lengthi16384e6:pieces0:ee";
+        assert_eq!(parse_files(data).unwrap(), vec![file("disk.iso", 42)]);
+        assert!(parse_files(b"not bencode").is_err());
+    }
+
+    #[test]
+    fn test_parse_layout() {
+        let data = b"d4:infod5:filesld6:lengthi3e4:pathl1:aeed6:lengthi5e4:pathl1:beee4:name1:r12:piece lengthi4e6:pieces40:AAAAAAAAAAAAAAAAAAAABBBBBBBBBBBBBBBBBBBBee";
+        let layout = parse_layout(data).unwrap();
+        assert_eq!(layout.piece_length, 4);
+        assert!(layout.has_pieces());
+        assert_eq!(layout.piece(1), Some(&[b'B'; 20][..]));
+        assert_eq!(layout.piece(2), None);
+        let offsets: Vec<(&str, u64)> = layout
+            .offsets()
+            .map(|(f, offset)| (f.path.as_str(), offset))
+            .collect();
+        assert_eq!(offsets, [("r/a", 0), ("r/b", 3)]);
+
+        // Без хешей частей сравнивать нечего
+        let data = b"d4:infod6:lengthi42e4:name8:disk.isoee";
+        assert!(!parse_layout(data).unwrap().has_pieces());
+    }
+
+    #[test]
+    fn test_diff_files() {
+        let old = [
+            file("A/1.mkv", 100),
+            file("A/2.mkv", 200),
+            file("A/old.srt", 1),
+        ];
+        let new = [
+            file("A/1.mkv", 100),
+            file("A/2.mkv", 250),
+            file("A/3.mkv", 300),
+        ];
+
+        assert_eq!(
+            diff_files(&old, &new),
+            vec![
+                "~ A/2.mkv (200 → 250 байт)".to_string(),
+                "- A/old.srt (1 байт)".to_string(),
+                "+ A/3.mkv (300 байт)".to_string(),
+            ]
+        );
+        assert!(diff_files(&old, &old).is_empty());
+    }
+}
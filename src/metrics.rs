@@ -0,0 +1,377 @@
+// src/metrics.rs
+
+//!
+//! Метрики в текстовом формате Prometheus: отдаются по HTTP (`/metrics`)
+//! и/или записываются в файл для textfile collector из node_exporter после каждого запуска
+//!
+
+use crate::http::{HttpStats, LATENCY_BUCKETS};
+use crate::report::{ReportAction, RunReport};
+use anyhow::{Context, Result};
+use axum::{extract::State, http::header, response::IntoResponse, routing::get, Router};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Настройки метрик (секция `[metrics]` в config.toml)
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// Адрес HTTP-сервера с `/metrics`, например `127.0.0.1:9187` (только в режиме `daemon`)
+    pub listen: Option<String>,
+    /// Файл для textfile collector из node_exporter (должен оканчиваться на `.prom`)
+    pub textfile_path: Option<String>,
+}
+
+impl MetricsConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.listen.is_some() || self.textfile_path.is_some()
+    }
+}
+
+/// Действия из отчета, которые считаются в `tracker_updater_actions_total`
+const COUNTED_ACTIONS: [(ReportAction, &str); 11] = [
+    (ReportAction::Updated, "updated"),
+    (ReportAction::Staged, "staged"),
+    (ReportAction::Removed, "removed"),
+    (ReportAction::Paused, "paused"),
+    (ReportAction::Tagged, "tagged"),
+    (ReportAction::Notified, "notified"),
+    (ReportAction::Unknown, "unknown"),
+    (ReportAction::Refused, "refused"),
+    (ReportAction::Failed, "failed"),
+    (ReportAction::Interrupted, "interrupted"),
+    (ReportAction::Deferred, "deferred"),
+];
+
+#[derive(Default)]
+struct Counters {
+    runs_succeeded: u64,
+    runs_failed: u64,
+    torrents_checked: u64,
+    actions: BTreeMap<&'static str, u64>,
+    last_run_timestamp: u64,
+    last_success_timestamp: u64,
+    last_run_duration: f64,
+    last_checked: usize,
+    seeders: BTreeMap<String, usize>,
+}
+
+/// Накопленные за время работы процесса метрики. Общие для сервера и цикла запусков.
+pub struct Metrics {
+    http: Arc<HttpStats>,
+    state: Mutex<Counters>,
+}
+
+impl Metrics {
+    pub fn new(http: Arc<HttpStats>) -> Self {
+        Metrics {
+            http,
+            state: Mutex::new(Counters::default()),
+        }
+    }
+
+    /// Учитывает успешный запуск
+    pub fn record_run(&self, report: &RunReport, duration: Duration) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.runs_succeeded += 1;
+        state.torrents_checked += report.checked as u64;
+        // Пробный запуск ничего не меняет: его действия не считаются
+        for (action, label) in COUNTED_ACTIONS {
+            let count = if report.dry_run {
+                0
+            } else {
+                report.count(action) as u64
+            };
+            *state.actions.entry(label).or_default() += count;
+        }
+        state.last_run_timestamp = report.started_at;
+        state.last_success_timestamp = report.started_at;
+        state.last_run_duration = duration.as_secs_f64();
+        state.last_checked = report.checked;
+        state.seeders = report.seeders.clone();
+    }
+
+    /// Учитывает запуск, прерванный ошибкой
+    pub fn record_failure(&self, started_at: u64, duration: Duration) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.runs_failed += 1;
+        state.last_run_timestamp = started_at;
+        state.last_run_duration = duration.as_secs_f64();
+    }
+
+    /// Метрики в текстовом формате Prometheus
+    pub fn render(&self) -> String {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+
+        header_line(
+            &mut out,
+            "tracker_updater_runs_total",
+            "counter",
+            "Запуски проверки",
+        );
+        let _ = writeln!(
+            out,
+            "tracker_updater_runs_total{{result=\"success\"}} {}",
+            state.runs_succeeded
+        );
+        let _ = writeln!(
+            out,
+            "tracker_updater_runs_total{{result=\"failure\"}} {}",
+            state.runs_failed
+        );
+
+        header_line(
+            &mut out,
+            "tracker_updater_torrents_checked_total",
+            "counter",
+            "Торренты, проверенные на трекерах",
+        );
+        let _ = writeln!(
+            out,
+            "tracker_updater_torrents_checked_total {}",
+            state.torrents_checked
+        );
+
+        header_line(
+            &mut out,
+            "tracker_updater_last_run_torrents_checked",
+            "gauge",
+            "Торренты, проверенные в последнем запуске",
+        );
+        let _ = writeln!(
+            out,
+            "tracker_updater_last_run_torrents_checked {}",
+            state.last_checked
+        );
+
+        header_line(
+            &mut out,
+            "tracker_updater_actions_total",
+            "counter",
+            "Действия с торрентами по типам",
+        );
+        for (label, value) in &state.actions {
+            let _ = writeln!(
+                out,
+                "tracker_updater_actions_total{{action=\"{}\"}} {}",
+                label, value
+            );
+        }
+
+        header_line(
+            &mut out,
+            "tracker_updater_last_run_timestamp_seconds",
+            "gauge",
+            "Время начала последнего запуска",
+        );
+        let _ = writeln!(
+            out,
+            "tracker_updater_last_run_timestamp_seconds {}",
+            state.last_run_timestamp
+        );
+
+        header_line(
+            &mut out,
+            "tracker_updater_last_success_timestamp_seconds",
+            "gauge",
+            "Время начала последнего успешного запуска",
+        );
+        let _ = writeln!(
+            out,
+            "tracker_updater_last_success_timestamp_seconds {}",
+            state.last_success_timestamp
+        );
+
+        header_line(
+            &mut out,
+            "tracker_updater_last_run_duration_seconds",
+            "gauge",
+            "Длительность последнего запуска",
+        );
+        let _ = writeln!(
+            out,
+            "tracker_updater_last_run_duration_seconds {:.3}",
+            state.last_run_duration
+        );
+
+        header_line(
+            &mut out,
+            "tracker_updater_torrents_by_seeders",
+            "gauge",
+            "Актуальные раздачи по числу сидов на трекере",
+        );
+        for (group, count) in &state.seeders {
+            let _ = writeln!(
+                out,
+                "tracker_updater_torrents_by_seeders{{seeders=\"{}\"}} {}",
+                group, count
+            );
+        }
+
+        header_line(
+            &mut out,
+            "tracker_updater_api_requests_total",
+            "counter",
+            "HTTP-запросы к трекерам, включая повторы",
+        );
+        let _ = writeln!(
+            out,
+            "tracker_updater_api_requests_total {}",
+            self.http.requests()
+        );
+
+        header_line(
+            &mut out,
+            "tracker_updater_api_errors_total",
+            "counter",
+            "HTTP-запросы к трекерам с сетевой ошибкой или статусом 4xx/5xx",
+        );
+        let _ = writeln!(
+            out,
+            "tracker_updater_api_errors_total {}",
+            self.http.errors()
+        );
+
+        header_line(
+            &mut out,
+            "tracker_updater_api_request_duration_seconds",
+            "histogram",
+            "Длительность HTTP-запросов к трекерам",
+        );
+        for (upper, count) in LATENCY_BUCKETS.iter().zip(self.http.latency_cumulative()) {
+            let _ = writeln!(
+                out,
+                "tracker_updater_api_request_duration_seconds_bucket{{le=\"{}\"}} {}",
+                upper, count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "tracker_updater_api_request_duration_seconds_bucket{{le=\"+Inf\"}} {}",
+            self.http.requests()
+        );
+        let _ = writeln!(
+            out,
+            "tracker_updater_api_request_duration_seconds_sum {:.6}",
+            self.http.latency_sum_secs()
+        );
+        let _ = writeln!(
+            out,
+            "tracker_updater_api_request_duration_seconds_count {}",
+            self.http.requests()
+        );
+
+        out
+    }
+
+    /// Атомарно записывает метрики в файл (через временный файл и переименование),
+    /// чтобы node_exporter никогда не прочитал его наполовину
+    pub fn write_textfile(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("❌ Не удалось создать папку {}", dir.display()))?;
+        }
+        let temp = path.with_extension("prom.tmp");
+        std::fs::write(&temp, self.render())
+            .with_context(|| format!("❌ Не удалось записать метрики в {}", temp.display()))?;
+        std::fs::rename(&temp, path)
+            .with_context(|| format!("❌ Не удалось записать метрики в {}", path.display()))
+    }
+}
+
+fn header_line(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Запускает HTTP-сервер с `/metrics` в фоне
+pub async fn serve(listen: &str, metrics: Arc<Metrics>) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("❌ Не удалось открыть адрес метрик {}", listen))?;
+    log::info!("📈 Метрики доступны по адресу http://{}/metrics", listen);
+
+    let app = Router::new()
+        .route("/metrics", get(metrics_handler))
+        .with_state(metrics);
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            log::error!("❌ Сервер метрик остановлен: {}", e);
+        }
+    });
+    Ok(())
+}
+
+async fn metrics_handler(State(metrics): State<Arc<Metrics>>) -> impl IntoResponse {
+    (
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        metrics.render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::ReportEntry;
+    use crate::torrent::Torrent;
+
+    fn torrent() -> Torrent {
+        Torrent {
+            name: "test".into(),
+            torrent_hash: "aa".into(),
+            torrent_id: None,
+            tracker: "".into(),
+            comment: "".into(),
+            state: "".into(),
+            category: "".into(),
+            tags: "".into(),
+            size: 0,
+            seeders: 0,
+            leechers: 0,
+            save_path: "".into(),
+            topic_info: None,
+        }
+    }
+
+    #[test]
+    fn test_render_after_runs() {
+        let metrics = Metrics::new(Arc::new(HttpStats::default()));
+
+        let mut report = RunReport::new(false);
+        report.checked = 5;
+        report.seeders.insert("0".into(), 2);
+        report.seeders.insert("4+".into(), 3);
+        report.push(ReportEntry::new(
+            "Rutracker",
+            &torrent(),
+            ReportAction::Updated,
+        ));
+        metrics.record_run(&report, Duration::from_millis(1500));
+        metrics.record_run(&report, Duration::from_millis(1500));
+        report.dry_run = true;
+        metrics.record_run(&report, Duration::from_millis(1500));
+        metrics.record_failure(report.started_at + 60, Duration::from_secs(1));
+
+        let text = metrics.render();
+        assert!(text.contains("tracker_updater_runs_total{result=\"success\"} 3\n"));
+        assert!(text.contains("tracker_updater_runs_total{result=\"failure\"} 1\n"));
+        assert!(text.contains("tracker_updater_torrents_checked_total 15\n"));
+        assert!(text.contains("tracker_updater_actions_total{action=\"updated\"} 2\n"));
+        assert!(text.contains("tracker_updater_actions_total{action=\"removed\"} 0\n"));
+        assert!(text.contains("tracker_updater_torrents_by_seeders{seeders=\"4+\"} 3\n"));
+        assert!(text.contains(&format!(
+            "tracker_updater_last_success_timestamp_seconds {}\n",
+            report.started_at
+        )));
+        assert!(text.contains("tracker_updater_api_request_duration_seconds_count 0\n"));
+    }
+}
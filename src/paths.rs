@@ -0,0 +1,201 @@
+// src/paths.rs
+
+//!
+//! Поиск файла конфигурации и разрешение путей, чтобы утилита работала одинаково
+//! независимо от текущей папки (например, при запуске из планировщика)
+//!
+
+use anyhow::Result;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Переменная окружения с путем к файлу конфигурации
+pub const CONFIG_ENV: &str = "TRACKER_UPDATER_CONFIG";
+
+/// Имя файла конфигурации при поиске по папкам
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Имя папки приложения в XDG-каталогах
+const APP_DIR: &str = "tracker-updater";
+
+/// Где искать файл конфигурации
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLookup {
+    /// Путь из флага `--config`
+    pub cli: Option<PathBuf>,
+    /// Значение `TRACKER_UPDATER_CONFIG`
+    pub env: Option<PathBuf>,
+    /// Папка исполняемого файла
+    pub exe_dir: Option<PathBuf>,
+    /// XDG-каталоги конфигурации и данных по порядку приоритета
+    pub xdg_dirs: Vec<PathBuf>,
+    /// Текущая папка (для совместимости с прежним поведением)
+    pub current_dir: Option<PathBuf>,
+}
+
+impl ConfigLookup {
+    /// Порядок поиска из окружения процесса
+    pub fn from_env(cli: Option<PathBuf>) -> Self {
+        ConfigLookup {
+            cli,
+            env: env::var_os(CONFIG_ENV)
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from),
+            exe_dir: env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(Path::to_path_buf)),
+            xdg_dirs: xdg_dirs(),
+            current_dir: env::current_dir().ok(),
+        }
+    }
+
+    /// Находит файл конфигурации.
+    ///
+    /// Явно указанный путь (флаг или переменная окружения) должен существовать; иначе берется
+    /// первый найденный `config.toml` из папки программы, XDG-каталогов и текущей папки.
+    pub fn find(&self) -> Result<PathBuf> {
+        if let Some(path) = self.cli.as_ref().or(self.env.as_ref()) {
+            if path.is_file() {
+                return Ok(absolute(path));
+            }
+            anyhow::bail!(
+                "❌ Файл конфигурации {} не найден. Убедитесь, что файл существует.",
+                path.display()
+            );
+        }
+
+        let candidates = self.candidates();
+        if let Some(found) = candidates.iter().find(|p| p.is_file()) {
+            return Ok(absolute(found));
+        }
+
+        let searched: Vec<String> = candidates
+            .iter()
+            .map(|p| format!("  {}", p.display()))
+            .collect();
+        anyhow::bail!(
+            "❌ Файл конфигурации не найден. Укажите его флагом --config или переменной {}.\nИскали в:\n{}",
+            CONFIG_ENV,
+            searched.join("\n")
+        )
+    }
+
+    fn candidates(&self) -> Vec<PathBuf> {
+        self.exe_dir
+            .iter()
+            .chain(&self.xdg_dirs)
+            .chain(&self.current_dir)
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .collect()
+    }
+}
+
+/// `$XDG_CONFIG_HOME/tracker-updater`, `$XDG_CONFIG_DIRS/*/tracker-updater`
+/// и `$XDG_DATA_HOME/tracker-updater` (со значениями по умолчанию из спецификации XDG)
+fn xdg_dirs() -> Vec<PathBuf> {
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let var = |name: &str| {
+        env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+
+    let mut dirs = Vec::new();
+    if let Some(dir) = var("XDG_CONFIG_HOME").or_else(|| home.as_ref().map(|h| h.join(".config"))) {
+        dirs.push(dir.join(APP_DIR));
+    }
+    let config_dirs = env::var("XDG_CONFIG_DIRS")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "/etc/xdg".to_string());
+    dirs.extend(
+        config_dirs
+            .split(':')
+            .filter(|d| !d.is_empty())
+            .map(|d| Path::new(d).join(APP_DIR)),
+    );
+    if let Some(dir) = var("XDG_DATA_HOME").or_else(|| home.map(|h| h.join(".local/share"))) {
+        dirs.push(dir.join(APP_DIR));
+    }
+    dirs
+}
+
+/// Абсолютный путь (относительный считается от текущей папки)
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Разрешает путь из конфигурации относительно папки файла конфигурации
+pub fn resolve(base_dir: &Path, path: &str) -> String {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_string_lossy().into_owned()
+    } else {
+        base_dir.join(path).to_string_lossy().into_owned()
+    }
+}
+
+/// Временная папка для скачанных .torrent файлов
+pub fn download_dir() -> PathBuf {
+    env::temp_dir().join(APP_DIR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("tracker-updater-paths-{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_resolve() {
+        let base = Path::new("/etc/tracker-updater");
+        assert_eq!(
+            resolve(base, "cache/properties.json"),
+            "/etc/tracker-updater/cache/properties.json"
+        );
+        assert_eq!(resolve(base, "/var/lib/x.json"), "/var/lib/x.json");
+    }
+
+    #[test]
+    fn test_find_order() {
+        let exe_dir = temp_dir("exe");
+        let xdg_dir = temp_dir("xdg");
+        std::fs::write(xdg_dir.join(CONFIG_FILE_NAME), "").unwrap();
+
+        let lookup = ConfigLookup {
+            exe_dir: Some(exe_dir.clone()),
+            xdg_dirs: vec![xdg_dir.clone()],
+            ..Default::default()
+        };
+        assert_eq!(lookup.find().unwrap(), xdg_dir.join(CONFIG_FILE_NAME));
+
+        // Файл рядом с программой важнее XDG
+        std::fs::write(exe_dir.join(CONFIG_FILE_NAME), "").unwrap();
+        assert_eq!(lookup.find().unwrap(), exe_dir.join(CONFIG_FILE_NAME));
+
+        // Явно указанный, но отсутствующий файл — ошибка, без поиска дальше
+        let explicit = ConfigLookup {
+            env: Some(exe_dir.join("other.toml")),
+            ..lookup.clone()
+        };
+        assert!(explicit.find().is_err());
+
+        let _ = std::fs::remove_dir_all(&exe_dir);
+        let _ = std::fs::remove_dir_all(&xdg_dir);
+    }
+
+    #[test]
+    fn test_find_reports_searched_dirs() {
+        let lookup = ConfigLookup {
+            current_dir: Some(PathBuf::from("/nonexistent-dir")),
+            ..Default::default()
+        };
+        let error = lookup.find().unwrap_err().to_string();
+        assert!(error.contains("/nonexistent-dir/config.toml"));
+    }
+}
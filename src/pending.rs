@@ -0,0 +1,265 @@
+// src/pending.rs
+
+//!
+//! Двухфазное обновление: новая версия раздачи добавляется рядом со старой, а старый торрент
+//! удаляется в одном из следующих запусков, когда новая версия докачается
+//!
+
+use crate::recheck::is_checking;
+use crate::topic_id::TopicId;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Что делать со старым торрентом, пока новая версия не готова
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum KeepOld {
+    /// Продолжать раздавать
+    #[default]
+    Seed,
+    /// Остановить
+    Pause,
+}
+
+/// Настройки двухфазного обновления (секция `[two_phase]` в config.toml)
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TwoPhaseConfig {
+    /// Не удалять старый торрент сразу после добавления новой версии
+    pub enabled: bool,
+    pub keep_old: KeepOld,
+    /// Добавлять новую версию остановленной (ее запускают вручную)
+    pub add_paused: bool,
+    /// Папка для новой версии; не задана — папка старого торрента
+    pub save_path: Option<String>,
+    /// Доля скачанного новой версии, после которой удаляется старый торрент
+    pub min_progress: f64,
+    /// Удалять файлы старой версии (только если новая сохраняется в `save_path`)
+    pub delete_old_files: bool,
+    /// Где хранить незавершенные обновления между запусками
+    pub state_path: String,
+}
+
+impl Default for TwoPhaseConfig {
+    fn default() -> Self {
+        TwoPhaseConfig {
+            enabled: false,
+            keep_old: KeepOld::Seed,
+            add_paused: false,
+            save_path: None,
+            min_progress: 1.0,
+            delete_old_files: false,
+            state_path: "cache/pending.json".to_string(),
+        }
+    }
+}
+
+impl TwoPhaseConfig {
+    /// Отдельная папка для новой версии, если двухфазное обновление включено
+    pub fn new_save_path(&self) -> Option<&str> {
+        self.save_path.as_deref().filter(|_| self.enabled)
+    }
+
+    /// Старая версия раздается из той же папки, в которую добавляется новая
+    pub fn seeds_from_same_path(&self) -> bool {
+        self.enabled && self.keep_old == KeepOld::Seed && self.save_path.is_none()
+    }
+
+    /// Проверяет, что новая версия не перезапишет файлы, которые раздает старая
+    pub fn validate(&self) -> Result<()> {
+        if self.seeds_from_same_path() && !self.add_paused {
+            anyhow::bail!(
+                "❌ [two_phase]: с keep_old = \"seed\" новая версия перезапишет файлы, которые \
+                 раздает старая. Задайте save_path, add_paused = true или keep_old = \"pause\""
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Незавершенное обновление: новая версия добавлена, старый торрент еще в клиенте
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PendingUpdate {
+    pub tracker: String,
+    pub name: String,
+    pub topic_id: Option<TopicId>,
+    pub new_hash: String,
+    /// Старый торрент остановлен при добавлении новой версии
+    pub old_paused: bool,
+    /// Удалять файлы вместе со старым торрентом
+    pub delete_files: bool,
+    /// Время добавления новой версии (unix time)
+    pub added_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PendingUpdates {
+    /// Хеш старого торрента → незавершенное обновление
+    updates: BTreeMap<String, PendingUpdate>,
+}
+
+impl PendingUpdates {
+    /// Загружает список. Отсутствующий файл дает пустой список.
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read(path) {
+            Ok(data) => serde_json::from_slice(&data).with_context(|| {
+                format!(
+                    "❌ Некорректный список незавершенных обновлений {}",
+                    path.display()
+                )
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("❌ Не удалось прочитать {}", path.display())),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("❌ Не удалось создать папку {}", dir.display()))?;
+        }
+        let data = serde_json::to_vec_pretty(self)?;
+        std::fs::write(path, data).with_context(|| {
+            format!(
+                "❌ Не удалось сохранить список незавершенных обновлений {}",
+                path.display()
+            )
+        })
+    }
+
+    pub fn contains(&self, old_hash: &str) -> bool {
+        self.updates.contains_key(old_hash)
+    }
+
+    pub fn insert(&mut self, old_hash: String, update: PendingUpdate) {
+        self.updates.insert(old_hash, update);
+    }
+
+    pub fn remove(&mut self, old_hash: &str) -> Option<PendingUpdate> {
+        self.updates.remove(old_hash)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &PendingUpdate)> {
+        self.updates.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.updates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.updates.is_empty()
+    }
+}
+
+/// Что делать с незавершенным обновлением в этом запуске
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// Новая версия еще не готова (доля скачанного)
+    Wait(f64),
+    /// Новая версия готова, старый торрент можно удалять
+    Finish,
+    /// Новой версии нет в клиенте — обновление отменено
+    NewMissing,
+    /// Старый торрент уже удален (например, вручную)
+    OldMissing,
+}
+
+/// Следующий шаг: `old_present` — есть ли старый торрент в клиенте, `new` — новая версия
+pub fn next_step(
+    old_present: bool,
+    new: Option<&qbit_rs::model::Torrent>,
+    min_progress: f64,
+) -> Step {
+    let Some(new) = new else {
+        return Step::NewMissing;
+    };
+    if !old_present {
+        return Step::OldMissing;
+    }
+    let progress = new.progress.unwrap_or(0.0);
+    let checking = new.state.as_ref().is_none_or(is_checking);
+    if !checking && progress >= min_progress {
+        Step::Finish
+    } else {
+        Step::Wait(progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qbit_rs::model::Torrent;
+
+    fn torrent(state: &str, progress: f64) -> Torrent {
+        serde_json::from_value(serde_json::json!({ "state": state, "progress": progress })).unwrap()
+    }
+
+    #[test]
+    fn test_next_step() {
+        let done = torrent("uploading", 1.0);
+        assert_eq!(next_step(true, Some(&done), 1.0), Step::Finish);
+        assert_eq!(next_step(false, Some(&done), 1.0), Step::OldMissing);
+        assert_eq!(next_step(true, None, 1.0), Step::NewMissing);
+
+        let downloading = torrent("downloading", 0.4);
+        assert_eq!(next_step(true, Some(&downloading), 1.0), Step::Wait(0.4));
+        assert_eq!(next_step(true, Some(&downloading), 0.3), Step::Finish);
+
+        // Пока идет проверка, доля скачанного еще не окончательная
+        let checking = torrent("checkingDL", 0.9);
+        assert_eq!(next_step(true, Some(&checking), 0.5), Step::Wait(0.9));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config = TwoPhaseConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        config.add_paused = true;
+        assert!(config.validate().is_ok());
+
+        config.add_paused = false;
+        config.save_path = Some("/data/new".into());
+        assert!(config.validate().is_ok());
+
+        config.save_path = None;
+        config.keep_old = KeepOld::Pause;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_pending_roundtrip() {
+        let path = Path::new("pending.temp_roundtrip.json");
+        let _ = std::fs::remove_file(path);
+
+        let mut pending = PendingUpdates::load(path).unwrap();
+        assert!(pending.is_empty());
+
+        pending.insert(
+            "old".into(),
+            PendingUpdate {
+                tracker: "rutracker".into(),
+                name: "test".into(),
+                topic_id: None,
+                new_hash: "new".into(),
+                old_paused: true,
+                delete_files: false,
+                added_at: 1,
+            },
+        );
+        pending.save(path).unwrap();
+
+        let mut loaded = PendingUpdates::load(path).unwrap();
+        assert!(loaded.contains("old"));
+        assert_eq!(loaded.remove("old").unwrap().new_hash, "new");
+        assert!(loaded.is_empty());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,281 @@
+// src/plan.rs
+
+//!
+//! План действий: `plan` сохраняет решения по торрентам в файл, `apply` выполняет именно их,
+//! предварительно убедившись, что ни qBittorrent, ни трекер с тех пор не изменились
+//!
+
+use crate::rules::{OutcomeKind, RuleAction};
+use crate::topic_id::TopicId;
+use crate::topic_info::TopicInfo;
+use crate::torrent::Torrent;
+use crate::trackers::CheckOutcome;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Версия формата файла плана
+const PLAN_VERSION: u32 = 1;
+
+/// Одно запланированное действие
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlanEntry {
+    /// Имя трекера (`TrackerProvider::name`)
+    pub tracker: String,
+    pub name: String,
+    /// Хеш торрента в qBittorrent на момент планирования
+    pub hash: String,
+    pub topic_id: Option<TopicId>,
+    /// Итог проверки на трекере
+    pub outcome: OutcomeKind,
+    /// Новый хеш раздачи (для обновленных раздач)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_hash: Option<String>,
+    pub action: RuleAction,
+    /// Сработавшее правило
+    pub rule: String,
+    /// Человекочитаемая причина (итог проверки)
+    pub reason: String,
+    /// Состояние торрента в qBittorrent, которое проверяется перед выполнением
+    pub save_path: String,
+    pub category: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_topic: Option<TopicInfo>,
+}
+
+impl PlanEntry {
+    pub fn new(
+        tracker: &str,
+        torrent: &Torrent,
+        outcome: &CheckOutcome,
+        action: RuleAction,
+        rule: String,
+        changes: Vec<String>,
+    ) -> Self {
+        let (outcome_kind, new_hash, new_topic) = match outcome {
+            CheckOutcome::Changed { new_hash, topic } => {
+                (OutcomeKind::Changed, Some(new_hash.clone()), topic.clone())
+            }
+            CheckOutcome::Removed => (OutcomeKind::Removed, None, None),
+            _ => (OutcomeKind::Current, None, None),
+        };
+
+        PlanEntry {
+            tracker: tracker.to_string(),
+            name: torrent.name.clone(),
+            hash: torrent.torrent_hash.clone(),
+            topic_id: torrent.torrent_id,
+            outcome: outcome_kind,
+            new_hash,
+            action,
+            rule,
+            reason: outcome.to_string(),
+            save_path: torrent.save_path.clone(),
+            category: torrent.category.clone(),
+            changes,
+            new_topic,
+        }
+    }
+
+    /// Проверяет, что торрент в qBittorrent все еще тот же, что при планировании
+    pub fn verify_client_state(&self, current: Option<&Torrent>) -> Result<(), PlanMismatch> {
+        let Some(current) = current else {
+            return Err(PlanMismatch::MissingInClient);
+        };
+
+        let mut differences = Vec::new();
+        if current.save_path != self.save_path {
+            differences.push(format!(
+                "путь: '{}' → '{}'",
+                self.save_path, current.save_path
+            ));
+        }
+        if current.category != self.category {
+            differences.push(format!(
+                "категория: '{}' → '{}'",
+                self.category, current.category
+            ));
+        }
+        if current.torrent_id != self.topic_id {
+            differences.push("ID темы в комментарии".to_string());
+        }
+
+        if differences.is_empty() {
+            Ok(())
+        } else {
+            Err(PlanMismatch::ClientChanged(differences.join(", ")))
+        }
+    }
+
+    /// Проверяет, что трекер отвечает так же, как при планировании
+    pub fn verify_outcome(&self, outcome: Option<&CheckOutcome>) -> Result<(), PlanMismatch> {
+        let matches = match (self.outcome, outcome) {
+            (OutcomeKind::Current, Some(CheckOutcome::Current)) => true,
+            (OutcomeKind::Removed, Some(CheckOutcome::Removed)) => true,
+            (OutcomeKind::Changed, Some(CheckOutcome::Changed { new_hash, .. })) => {
+                self.new_hash.as_deref() == Some(new_hash.as_str())
+            }
+            _ => false,
+        };
+
+        if matches {
+            Ok(())
+        } else {
+            Err(PlanMismatch::TrackerChanged(
+                outcome
+                    .map(|o| o.to_string())
+                    .unwrap_or_else(|| "нет ответа".to_string()),
+            ))
+        }
+    }
+}
+
+/// Почему запланированное действие не может быть выполнено
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PlanMismatch {
+    #[error("торрента больше нет в qBittorrent")]
+    MissingInClient,
+    #[error("торрент в qBittorrent изменился ({0})")]
+    ClientChanged(String),
+    #[error("ответ трекера изменился: {0}")]
+    TrackerChanged(String),
+    #[error("торрент ждет завершения двухфазного обновления")]
+    PendingUpdate,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Plan {
+    pub version: u32,
+    /// Время составления плана (unix time)
+    pub created_at: u64,
+    pub entries: Vec<PlanEntry>,
+}
+
+impl Default for Plan {
+    fn default() -> Self {
+        Plan {
+            version: PLAN_VERSION,
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl Plan {
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read(path)
+            .with_context(|| format!("❌ Не удалось прочитать план {}", path.display()))?;
+        let plan: Plan = serde_json::from_slice(&data)
+            .with_context(|| format!("❌ Некорректный файл плана {}", path.display()))?;
+
+        if plan.version != PLAN_VERSION {
+            anyhow::bail!(
+                "❌ План {} сохранен в неподдерживаемом формате (версия {})",
+                path.display(),
+                plan.version
+            );
+        }
+        Ok(plan)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("❌ Не удалось создать папку {}", dir.display()))?;
+        }
+        let data = serde_json::to_vec_pretty(self)?;
+        std::fs::write(path, data)
+            .with_context(|| format!("❌ Не удалось сохранить план {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn torrent() -> Torrent {
+        Torrent {
+            name: "test".into(),
+            torrent_hash: "aa".into(),
+            torrent_id: Some(TopicId::new(1)),
+            tracker: "".into(),
+            comment: "".into(),
+            state: "".into(),
+            category: "linux".into(),
+            tags: "".into(),
+            size: 0,
+            seeders: 0,
+            leechers: 0,
+            save_path: "/data".into(),
+            topic_info: None,
+        }
+    }
+
+    fn changed(hash: &str) -> CheckOutcome {
+        CheckOutcome::Changed {
+            new_hash: hash.into(),
+            topic: None,
+        }
+    }
+
+    fn entry() -> PlanEntry {
+        PlanEntry::new(
+            "Rutracker",
+            &torrent(),
+            &changed("bb"),
+            RuleAction::Update,
+            "обновление раздачи".into(),
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_verify_client_state() {
+        let entry = entry();
+        assert_eq!(entry.verify_client_state(Some(&torrent())), Ok(()));
+        assert_eq!(
+            entry.verify_client_state(None),
+            Err(PlanMismatch::MissingInClient)
+        );
+
+        let moved = Torrent {
+            save_path: "/other".into(),
+            ..torrent()
+        };
+        assert!(matches!(
+            entry.verify_client_state(Some(&moved)),
+            Err(PlanMismatch::ClientChanged(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_outcome() {
+        let entry = entry();
+        assert_eq!(entry.verify_outcome(Some(&changed("bb"))), Ok(()));
+        // Раздачу успели перезалить еще раз
+        assert!(entry.verify_outcome(Some(&changed("cc"))).is_err());
+        assert!(entry.verify_outcome(Some(&CheckOutcome::Removed)).is_err());
+        assert!(entry.verify_outcome(None).is_err());
+    }
+
+    #[test]
+    fn test_plan_roundtrip() {
+        let path = Path::new("plan.temp_roundtrip.json");
+        let plan = Plan {
+            entries: vec![entry()],
+            ..Default::default()
+        };
+        plan.save(path).unwrap();
+
+        let loaded = Plan::load(path).unwrap();
+        assert_eq!(loaded.entries, plan.entries);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,186 @@
+// src/recheck.rs
+
+//!
+//! Контроль проверки данных после обновления: qBittorrent перепроверяет уже скачанные файлы
+//! нового торрента, а мы дожидаемся результата и сравниваем, сколько данных нашлось
+//!
+
+use crate::shutdown::Shutdown;
+use anyhow::{Context, Result};
+use qbit_rs::model::{GetTorrentListArg, State};
+use qbit_rs::Qbit;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Настройки контроля проверки (секция `[recheck]` в config.toml)
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RecheckConfig {
+    /// Дожидаться окончания проверки нового торрента после обновления
+    pub enabled: bool,
+    /// Сколько ждать окончания проверки, секунды
+    pub timeout_secs: u64,
+    /// Как часто опрашивать qBittorrent, секунды
+    pub poll_secs: u64,
+    /// Минимальная доля ожидаемых данных, которая должна найтись на диске
+    pub min_ratio: f64,
+    /// Останавливать торрент, если данных нашлось слишком мало
+    pub pause_on_low: bool,
+    /// Куда сохранять .torrent старой версии на время проверки
+    pub archive_dir: String,
+}
+
+impl Default for RecheckConfig {
+    fn default() -> Self {
+        RecheckConfig {
+            enabled: false,
+            timeout_secs: 30 * 60,
+            poll_secs: 10,
+            min_ratio: 0.5,
+            pause_on_low: true,
+            archive_dir: "cache/archive".to_string(),
+        }
+    }
+}
+
+/// Итог проверки нового торрента
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Recheck {
+    /// Дождались ли окончания проверки
+    pub finished: bool,
+    /// Доля нового торрента, найденная на диске (0..1)
+    pub progress: f64,
+    /// Сколько байт нового торрента уже на диске
+    pub present_bytes: u64,
+    /// Размер нового торрента
+    pub size: u64,
+    /// Сколько данных ожидалось найти (не больше размера старой версии)
+    pub expected_bytes: u64,
+    /// Данных нашлось заметно меньше ожидаемого
+    pub low: bool,
+    /// Торрент остановлен из-за нехватки данных
+    pub paused: bool,
+    /// Сохраненный .torrent старой версии для восстановления
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<String>,
+}
+
+impl Recheck {
+    /// Оценивает результат проверки: `old_size` — размер старой версии раздачи
+    pub fn evaluate(progress: f64, size: u64, old_size: u64, min_ratio: f64) -> Self {
+        let progress = progress.clamp(0.0, 1.0);
+        let present_bytes = (progress * size as f64).round() as u64;
+        let expected_bytes = old_size.min(size);
+        Recheck {
+            finished: true,
+            progress,
+            present_bytes,
+            size,
+            expected_bytes,
+            low: (present_bytes as f64) < expected_bytes as f64 * min_ratio,
+            paused: false,
+            archive: None,
+        }
+    }
+}
+
+/// Идет ли еще проверка (или подготовка к ней)
+pub fn is_checking(state: &State) -> bool {
+    matches!(
+        state,
+        State::CheckingUP
+            | State::CheckingDL
+            | State::CheckingResumeData
+            | State::Allocating
+            | State::MetaDL
+            | State::Moving
+    )
+}
+
+/// Ждет окончания проверки торрента и возвращает `(progress, size)`.
+///
+/// `None` — не дождались: истекло время или запрошена остановка.
+pub async fn wait_recheck(
+    client: &Qbit,
+    hash: &str,
+    config: &RecheckConfig,
+    shutdown: &Shutdown,
+) -> Result<Option<(f64, u64)>> {
+    let deadline = Instant::now() + Duration::from_secs(config.timeout_secs);
+    let poll = Duration::from_secs(config.poll_secs.max(1));
+
+    loop {
+        let list = client
+            .get_torrent_list(GetTorrentListArg {
+                hashes: Some(hash.to_string()),
+                ..Default::default()
+            })
+            .await
+            .context("❌ Не удалось получить состояние нового торрента")?;
+
+        // Сразу после добавления торрента может еще не быть в списке
+        if let Some(torrent) = list.first() {
+            let checking = torrent.state.as_ref().is_none_or(is_checking);
+            if !checking {
+                let size = torrent.size.or(torrent.total_size).unwrap_or(0).max(0) as u64;
+                return Ok(Some((torrent.progress.unwrap_or(0.0), size)));
+            }
+        }
+
+        if Instant::now() + poll > deadline {
+            return Ok(None);
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(poll) => {}
+            _ = shutdown.requested() => return Ok(None),
+        }
+    }
+}
+
+/// Сохраняет .torrent торрента из qBittorrent в `dir` под именем `<хеш>.torrent`
+pub async fn archive_torrent(client: &Qbit, hash: &str, dir: &Path) -> Result<PathBuf> {
+    let data = client
+        .export_torrent(hash)
+        .await
+        .with_context(|| format!("❌ Не удалось выгрузить .torrent для {}", hash))?;
+    tokio::fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("❌ Не удалось создать папку {}", dir.display()))?;
+    let path = dir.join(format!("{}.torrent", hash));
+    tokio::fs::write(&path, &data)
+        .await
+        .with_context(|| format!("❌ Не удалось сохранить {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        // Обновление добавило файл: старые 800 байт на месте
+        let recheck = Recheck::evaluate(0.8, 1000, 800, 0.5);
+        assert_eq!(recheck.present_bytes, 800);
+        assert_eq!(recheck.expected_bytes, 800);
+        assert!(!recheck.low);
+
+        // Данных почти нет — вероятно, неверная папка сохранения
+        let recheck = Recheck::evaluate(0.1, 1000, 1000, 0.5);
+        assert!(recheck.low);
+
+        // Раздача уменьшилась: ожидаем не больше нового размера
+        let recheck = Recheck::evaluate(1.0, 500, 1000, 0.5);
+        assert_eq!(recheck.expected_bytes, 500);
+        assert!(!recheck.low);
+    }
+
+    #[test]
+    fn test_is_checking() {
+        assert!(is_checking(&State::CheckingResumeData));
+        assert!(is_checking(&State::CheckingDL));
+        assert!(!is_checking(&State::PausedDL));
+        assert!(!is_checking(&State::Uploading));
+    }
+}
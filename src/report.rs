@@ -0,0 +1,200 @@
+// src/report.rs
+
+//!
+//! Отчет о запуске: что было сделано с каждым торрентом и что изменилось в теме
+//!
+
+use crate::pending::PendingUpdate;
+use crate::plan::PlanEntry;
+use crate::recheck::Recheck;
+use crate::topic_id::TopicId;
+use crate::topic_info::TopicInfo;
+use crate::torrent::Torrent;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Что произошло с торрентом
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportAction {
+    Updated,
+    /// Новая версия добавлена, старый торрент удаляется после ее докачки (`[two_phase]`)
+    Staged,
+    Removed,
+    Paused,
+    Tagged,
+    Notified,
+    /// Раздача без сидов дольше заданного срока (только в отчете)
+    NoSeeders,
+    Unknown,
+    /// Действие из плана не выполнено: состояние изменилось после планирования
+    Refused,
+    Failed,
+    /// Действие не выполнено: работа остановлена по сигналу
+    Interrupted,
+    /// Действие отложено до следующего запуска (например, мало места на диске)
+    Deferred,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ReportEntry {
+    pub tracker: String,
+    pub name: String,
+    pub hash: String,
+    pub topic_id: Option<TopicId>,
+    pub action: ReportAction,
+    /// Правило, по которому выбрано действие
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_hash: Option<String>,
+    /// Сведения о теме до обновления (последние известные)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_topic: Option<TopicInfo>,
+    /// Сведения о теме после обновления
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_topic: Option<TopicInfo>,
+    /// Человекочитаемые отличия: переименование, перенос, смена размера
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<String>,
+    /// Сколько дней на трекере нет сидов
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days_without_seeders: Option<u64>,
+    /// Итог проверки данных после обновления
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recheck: Option<Recheck>,
+    /// Папка с сохраненной предыдущей версией файлов
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preserved: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ReportEntry {
+    pub fn new(tracker: &str, torrent: &Torrent, action: ReportAction) -> Self {
+        ReportEntry {
+            tracker: tracker.to_string(),
+            name: torrent.name.clone(),
+            hash: torrent.torrent_hash.clone(),
+            topic_id: torrent.torrent_id,
+            action,
+            rule: None,
+            new_hash: None,
+            old_topic: torrent.topic_info.clone(),
+            new_topic: None,
+            changes: Vec::new(),
+            days_without_seeders: None,
+            recheck: None,
+            preserved: None,
+            error: None,
+        }
+    }
+
+    /// Запись по действию из плана (когда торрента в клиенте уже может не быть)
+    pub fn from_plan(tracker: &str, entry: &PlanEntry, action: ReportAction) -> Self {
+        ReportEntry {
+            tracker: tracker.to_string(),
+            name: entry.name.clone(),
+            hash: entry.hash.clone(),
+            topic_id: entry.topic_id,
+            action,
+            rule: Some(entry.rule.clone()),
+            new_hash: entry.new_hash.clone(),
+            old_topic: None,
+            new_topic: entry.new_topic.clone(),
+            changes: entry.changes.clone(),
+            days_without_seeders: None,
+            recheck: None,
+            preserved: None,
+            error: None,
+        }
+    }
+
+    /// Запись по незавершенному двухфазному обновлению (`hash` — хеш старого торрента)
+    pub fn pending(hash: &str, update: &PendingUpdate, action: ReportAction) -> Self {
+        ReportEntry {
+            tracker: update.tracker.clone(),
+            name: update.name.clone(),
+            hash: hash.to_string(),
+            topic_id: update.topic_id,
+            action,
+            rule: None,
+            new_hash: Some(update.new_hash.clone()),
+            old_topic: None,
+            new_topic: None,
+            changes: Vec::new(),
+            days_without_seeders: None,
+            recheck: None,
+            preserved: None,
+            error: None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RunReport {
+    /// Время начала запуска (unix time)
+    pub started_at: u64,
+    pub dry_run: bool,
+    /// Сколько торрентов проверено на трекерах
+    pub checked: usize,
+    /// Число актуальных раздач по группам сидов (`KeeperConfig::seed_group`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub seeders: BTreeMap<String, usize>,
+    /// Запуск остановлен по сигналу, отчет неполный
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub interrupted: bool,
+    pub entries: Vec<ReportEntry>,
+}
+
+impl RunReport {
+    pub fn new(dry_run: bool) -> Self {
+        RunReport {
+            started_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            dry_run,
+            checked: 0,
+            seeders: BTreeMap::new(),
+            interrupted: false,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, entry: ReportEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn count(&self, action: ReportAction) -> usize {
+        self.entries.iter().filter(|e| e.action == action).count()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("❌ Не удалось создать папку {}", dir.display()))?;
+        }
+        let data = serde_json::to_vec_pretty(self)?;
+        std::fs::write(path, data)
+            .with_context(|| format!("❌ Не удалось сохранить отчет {}", path.display()))
+    }
+}
+
+/// Отличия новой версии темы от торрента в клиенте.
+///
+/// Если прежние сведения о теме неизвестны, сравнивается только размер.
+pub fn topic_changes(torrent: &Torrent, new: &TopicInfo) -> Vec<String> {
+    match &torrent.topic_info {
+        Some(old) => old.changes_to(new),
+        None if torrent.size != 0 && torrent.size != new.size => vec![format!(
+            "размер: {} MB → {} MB",
+            torrent.size / (1024 * 1024),
+            new.size / (1024 * 1024)
+        )],
+        None => Vec::new(),
+    }
+}
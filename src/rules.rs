@@ -0,0 +1,383 @@
+// src/rules.rs
+
+//!
+//! Правила, по которым решается, что делать с проверенным торрентом.
+//!
+//! Правила из секций `[[rules]]` проверяются по порядку, срабатывает первое подходящее.
+//! Решение принимается без обращения к qBittorrent, выполнение — отдельно в `lib.rs`.
+//!
+
+use crate::topic_info::{TopicInfo, TorStatus};
+use crate::torrent::Torrent;
+use crate::trackers::CheckOutcome;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Итог проверки, на который может ссылаться правило
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeKind {
+    Current,
+    Changed,
+    Removed,
+}
+
+/// Статус раздачи в условии правила: имя (`closed`, `duplicate`, ...) или код
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub struct StatusMatch(pub TorStatus);
+
+impl TryFrom<String> for StatusMatch {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse().map(StatusMatch)
+    }
+}
+
+/// Условия правила. Незаданные условия не проверяются, заданные должны выполниться все.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuleCondition {
+    pub outcome: Option<Vec<OutcomeKind>>,
+    pub tor_status: Option<Vec<StatusMatch>>,
+    pub category: Option<Vec<String>>,
+    /// Срабатывает, если у торрента есть хотя бы один из тегов
+    pub tags: Option<Vec<String>>,
+    /// Минимальное относительное изменение размера раздачи (0.1 = 10%)
+    pub min_size_change: Option<f64>,
+    /// Максимальное относительное изменение размера раздачи
+    pub max_size_change: Option<f64>,
+    pub min_seeders: Option<u32>,
+    pub max_seeders: Option<u32>,
+    /// Минимальный возраст раздачи на трекере в днях (по дате регистрации)
+    pub min_age_days: Option<u64>,
+    pub max_age_days: Option<u64>,
+}
+
+/// Действие правила
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Заменить торрент новой версией раздачи (только для обновленных раздач)
+    Update,
+    /// Удалить торрент вместе с файлами
+    Delete,
+    /// Остановить торрент
+    Pause,
+    /// Добавить тег
+    Tag(String),
+    /// Только сообщить в логе и отчете
+    Notify,
+    /// Ничего не делать
+    Ignore,
+}
+
+impl fmt::Display for RuleAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleAction::Update => write!(f, "обновить"),
+            RuleAction::Delete => write!(f, "удалить"),
+            RuleAction::Pause => write!(f, "остановить"),
+            RuleAction::Tag(tag) => write!(f, "добавить тег '{}'", tag),
+            RuleAction::Notify => write!(f, "сообщить"),
+            RuleAction::Ignore => write!(f, "пропустить"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    #[serde(default)]
+    pub when: RuleCondition,
+    pub action: RuleAction,
+}
+
+/// Решение по торренту: какое правило сработало и что нужно сделать
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decision {
+    pub rule: String,
+    pub action: RuleAction,
+}
+
+/// Правила по умолчанию повторяют прежнее поведение: новый хеш → обновить, раздачи нет → удалить
+pub fn default_rules() -> Vec<Rule> {
+    vec![
+        Rule {
+            name: "обновление раздачи".to_string(),
+            when: RuleCondition {
+                outcome: Some(vec![OutcomeKind::Changed]),
+                ..Default::default()
+            },
+            action: RuleAction::Update,
+        },
+        Rule {
+            name: "раздача удалена".to_string(),
+            when: RuleCondition {
+                outcome: Some(vec![OutcomeKind::Removed]),
+                ..Default::default()
+            },
+            action: RuleAction::Delete,
+        },
+    ]
+}
+
+/// Сведения о торренте, по которым проверяются условия
+struct Facts<'a> {
+    outcome: OutcomeKind,
+    torrent: &'a Torrent,
+    /// Актуальные сведения о теме (новая версия для обновленной раздачи)
+    topic: Option<&'a TopicInfo>,
+    now: i64,
+}
+
+impl Facts<'_> {
+    fn size_change(&self) -> Option<f64> {
+        if self.outcome != OutcomeKind::Changed {
+            return None;
+        }
+        let new = self.topic?.size;
+        let old = self
+            .torrent
+            .topic_info
+            .as_ref()
+            .map(|t| t.size)
+            .unwrap_or(self.torrent.size);
+        (old > 0).then(|| (new as f64 - old as f64).abs() / old as f64)
+    }
+
+    fn age_days(&self) -> Option<u64> {
+        let reg_time = self.topic?.reg_time;
+        (reg_time > 0).then(|| ((self.now - reg_time).max(0) / 86_400) as u64)
+    }
+
+    fn has_any_tag(&self, wanted: &[String]) -> bool {
+        self.torrent
+            .tags
+            .split(',')
+            .map(str::trim)
+            .any(|tag| wanted.iter().any(|w| w == tag))
+    }
+}
+
+/// Проверяет значение по необязательным границам; без значения условие не выполнено
+fn within<T: PartialOrd>(value: Option<T>, min: Option<T>, max: Option<T>) -> bool {
+    if min.is_none() && max.is_none() {
+        return true;
+    }
+    let Some(value) = value else {
+        return false;
+    };
+    min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+}
+
+impl Rule {
+    fn matches(&self, facts: &Facts) -> bool {
+        let when = &self.when;
+
+        // Обновить можно только раздачу, у которой есть новый хеш
+        if self.action == RuleAction::Update && facts.outcome != OutcomeKind::Changed {
+            return false;
+        }
+
+        when.outcome
+            .as_ref()
+            .is_none_or(|kinds| kinds.contains(&facts.outcome))
+            && when.tor_status.as_ref().is_none_or(|statuses| {
+                facts
+                    .topic
+                    .is_some_and(|t| statuses.contains(&StatusMatch(t.tor_status)))
+            })
+            && when
+                .category
+                .as_ref()
+                .is_none_or(|categories| categories.contains(&facts.torrent.category))
+            && when
+                .tags
+                .as_ref()
+                .is_none_or(|tags| facts.has_any_tag(tags))
+            && within(
+                facts.size_change(),
+                when.min_size_change,
+                when.max_size_change,
+            )
+            && within(
+                Some(facts.torrent.seeders),
+                when.min_seeders,
+                when.max_seeders,
+            )
+            && within(facts.age_days(), when.min_age_days, when.max_age_days)
+    }
+}
+
+/// Выбирает действие для проверенного торрента.
+///
+/// Для `Unknown` решение не принимается никогда: с непроверенными торрентами ничего не делаем.
+/// `None` — ни одно правило не подошло.
+pub fn decide(
+    rules: &[Rule],
+    torrent: &Torrent,
+    outcome: &CheckOutcome,
+    now: i64,
+) -> Option<Decision> {
+    let (kind, topic) = match outcome {
+        CheckOutcome::Current => (OutcomeKind::Current, torrent.topic_info.as_ref()),
+        CheckOutcome::Changed { topic, .. } => (OutcomeKind::Changed, topic.as_ref()),
+        CheckOutcome::Removed => (OutcomeKind::Removed, None),
+        CheckOutcome::Unknown { .. } => return None,
+    };
+    let facts = Facts {
+        outcome: kind,
+        torrent,
+        topic,
+        now,
+    };
+
+    rules
+        .iter()
+        .find(|rule| rule.matches(&facts))
+        .map(|rule| Decision {
+            rule: rule.name.clone(),
+            action: rule.action.clone(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+
+    fn topic(size: u64, status: TorStatus) -> TopicInfo {
+        TopicInfo {
+            topic_title: "test".into(),
+            forum_id: 1,
+            poster_id: 1,
+            size,
+            reg_time: 10 * DAY,
+            tor_status: status,
+            seeder_last_seen: 0,
+        }
+    }
+
+    fn torrent(size: u64, tags: &str) -> Torrent {
+        Torrent {
+            name: "test".into(),
+            torrent_hash: "aa".into(),
+            torrent_id: None,
+            tracker: "".into(),
+            comment: "".into(),
+            state: "".into(),
+            category: "Фильмы".into(),
+            tags: tags.into(),
+            size,
+            seeders: 3,
+            leechers: 0,
+            save_path: "".into(),
+            topic_info: None,
+        }
+    }
+
+    fn changed(size: u64) -> CheckOutcome {
+        CheckOutcome::Changed {
+            new_hash: "bb".into(),
+            topic: Some(topic(size, TorStatus::Verified)),
+        }
+    }
+
+    #[test]
+    fn test_default_rules_reproduce_hardwired_behavior() {
+        let rules = default_rules();
+        let t = torrent(100, "");
+
+        let update = decide(&rules, &t, &changed(100), 0).unwrap();
+        assert_eq!(update.action, RuleAction::Update);
+        let delete = decide(&rules, &t, &CheckOutcome::Removed, 0).unwrap();
+        assert_eq!(delete.action, RuleAction::Delete);
+
+        assert_eq!(decide(&rules, &t, &CheckOutcome::Current, 0), None);
+        let unknown = CheckOutcome::Unknown {
+            error: "timeout".into(),
+        };
+        assert_eq!(decide(&rules, &t, &unknown, 0), None);
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let rules = vec![
+            Rule {
+                name: "большие изменения".into(),
+                when: RuleCondition {
+                    min_size_change: Some(0.5),
+                    ..Default::default()
+                },
+                action: RuleAction::Notify,
+            },
+            Rule {
+                name: "закрытые".into(),
+                when: RuleCondition {
+                    tor_status: Some(vec![StatusMatch(TorStatus::Closed)]),
+                    ..Default::default()
+                },
+                action: RuleAction::Pause,
+            },
+            default_rules().remove(0),
+        ];
+        let t = torrent(100, "");
+
+        // Размер вырос вдвое — только уведомление
+        assert_eq!(
+            decide(&rules, &t, &changed(200), 0).unwrap().action,
+            RuleAction::Notify
+        );
+        // Небольшое изменение — обычное обновление
+        assert_eq!(
+            decide(&rules, &t, &changed(110), 0).unwrap().action,
+            RuleAction::Update
+        );
+
+        // Хеш актуален, но тема закрыта
+        let mut closed = torrent(100, "");
+        closed.topic_info = Some(topic(100, TorStatus::Closed));
+        let decision = decide(&rules, &closed, &CheckOutcome::Current, 0).unwrap();
+        assert_eq!(decision.rule, "закрытые");
+        assert_eq!(decision.action, RuleAction::Pause);
+    }
+
+    #[test]
+    fn test_conditions_tags_category_age() {
+        let rule = Rule {
+            name: "старые в архив".into(),
+            when: RuleCondition {
+                outcome: Some(vec![OutcomeKind::Changed]),
+                category: Some(vec!["Фильмы".into()]),
+                tags: Some(vec!["archive".into()]),
+                min_age_days: Some(30),
+                ..Default::default()
+            },
+            action: RuleAction::Tag("outdated".into()),
+        };
+        let rules = [rule];
+
+        let tagged = torrent(100, "hd, archive");
+        assert!(decide(&rules, &tagged, &changed(100), 5 * DAY).is_none());
+        assert_eq!(
+            decide(&rules, &tagged, &changed(100), 100 * DAY)
+                .unwrap()
+                .action,
+            RuleAction::Tag("outdated".into())
+        );
+        assert!(decide(&rules, &torrent(100, "hd"), &changed(100), 100 * DAY).is_none());
+    }
+
+    #[test]
+    fn test_update_never_matches_without_new_hash() {
+        let rules = [Rule {
+            name: "все подряд".into(),
+            when: RuleCondition::default(),
+            action: RuleAction::Update,
+        }];
+        assert!(decide(&rules, &torrent(0, ""), &CheckOutcome::Removed, 0).is_none());
+    }
+}
@@ -1,17 +1,28 @@
 // src/rutracker_api.rs
 
+use crate::http::HttpClient;
+use crate::topic_id::{find_topic_id, TopicId, TopicIdError, TopicUrlPattern};
+use crate::topic_info::TopicInfo;
 use crate::torrent;
+use crate::trackers::download_torrent_file;
 use anyhow::{Context, Result};
-use reqwest::header::{self, HeaderMap};
-use reqwest::Client;
+use futures::stream::{self, StreamExt};
+use reqwest::header::HeaderMap;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::fmt;
-use tokio::io::AsyncWriteExt;
 
 // --- КОНСТАНТЫ API ---
 const API_LIMIT_URL: &str = "https://api.rutracker.cc/v1/get_limit";
 const API_TOR_TOPIC_DATA_URL: &str = "https://api.rutracker.cc/v1/get_tor_topic_data";
+const API_PEER_STATS_URL: &str = "https://api.rutracker.cc/v1/get_peer_stats";
+
+/// Ссылки на темы Rutracker: `viewtopic.php?t=ID` и `dl.php?t=ID` на всех зеркалах
+const RUTRACKER_TOPIC_URL: TopicUrlPattern = TopicUrlPattern {
+    domains: &["rutracker.org", "rutracker.net", "rutracker.nl"],
+    scripts: &["viewtopic.php", "dl.php"],
+    param: "t",
+};
 
 // --- СТРУКТУРЫ ДЛЯ get_limit ---
 #[derive(Deserialize)]
@@ -24,9 +35,11 @@ struct ResultDataLimit {
     limit: u32,
 }
 
-pub async fn get_api_limit_async() -> Result<u32> {
-    let response_data: ApiResponseLimit = reqwest::get(API_LIMIT_URL)
+pub async fn get_api_limit_async(http: &HttpClient) -> Result<u32> {
+    let response_data: ApiResponseLimit = http
+        .get(API_LIMIT_URL, None)
         .await
+        .and_then(|r| r.error_for_status())
         .context("Ошибка при выполнении запроса к API лимитов")?
         .json::<ApiResponseLimit>()
         .await
@@ -35,28 +48,49 @@ pub async fn get_api_limit_async() -> Result<u32> {
     Ok(response_data.result.limit)
 }
 
-pub fn extract_torrent_id_from_comment(comment: &str) -> String {
-    comment
-        .rsplit_once("t=")
-        .map(|(_, id)| id.to_string())
-        .unwrap_or_default()
+/// Извлекает ID темы Rutracker из комментария торрента
+pub fn extract_torrent_id_from_comment(comment: &str) -> Result<TopicId, TopicIdError> {
+    find_topic_id(comment, &RUTRACKER_TOPIC_URL)
 }
 
-#[derive(Deserialize, Debug)]
-struct TopicData {
-    info_hash: String,
-    seeders: u32,
+/// Данные темы из get_tor_topic_data
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TopicData {
+    pub info_hash: String,
+    pub seeders: u32,
+    #[serde(flatten)]
+    pub info: TopicInfo,
+}
+
+/// Статистика пиров из get_peer_stats (API отдает массив `[сиды, личи, сид_последний_раз]`)
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(from = "(u32, u32, i64)")]
+pub struct PeerStats {
+    pub seeders: u32,
+    pub leechers: u32,
+    /// Когда в последний раз видели сида (unix time, 0 — неизвестно)
+    pub seeder_last_seen: i64,
+}
+
+impl From<(u32, u32, i64)> for PeerStats {
+    fn from((seeders, leechers, seeder_last_seen): (u32, u32, i64)) -> Self {
+        PeerStats {
+            seeders,
+            leechers,
+            seeder_last_seen,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug)]
-struct ApiResponseTopicData {
-    result: std::collections::HashMap<String, Option<TopicData>>,
+struct ApiResponseData<T> {
+    result: HashMap<String, Option<T>>,
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(untagged)]
-enum ApiResponse {
-    Success(ApiResponseTopicData),
+enum ApiResponse<T> {
+    Success(ApiResponseData<T>),
     Error(ApiErrorResponse),
 }
 
@@ -72,197 +106,391 @@ struct ApiErrorResponse {
     error: ApiErrorDetail,
 }
 
+/// Ошибки обращения к API Rutracker
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ApiError {
+    #[error("ошибка HTTP запроса: {0}")]
+    Http(String),
+    #[error("API вернул HTTP статус {0}")]
+    Status(u16),
+    #[error("не удалось разобрать ответ API: {0}")]
+    Decode(String),
+    #[error("ошибка API Rutracker: {0}")]
+    Api(String),
+    #[error("API не вернул данных для запрошенного значения")]
+    Missing,
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        if let Some(status) = e.status() {
+            ApiError::Status(status.as_u16())
+        } else if e.is_decode() {
+            ApiError::Decode(e.to_string())
+        } else {
+            ApiError::Http(e.to_string())
+        }
+    }
+}
+
+/// Результат поиска раздачи по хешу
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HashLookup {
+    /// Раздача с таким хешем есть на трекере (статистика обновлена)
+    Found,
+    /// API ответил, что раздачи с таким хешем нет
+    NotFound,
+    /// Проверить хеш не удалось
+    Failed(ApiError),
+}
+
 fn extract_invalid_hash(error_text: &str) -> Option<&str> {
     error_text
         .rsplit_once("Invalid hash: ")
         .map(|(_, hash)| hash.trim())
 }
 
+/// Запрос к методу API по списку значений; `by` — `hash` или `topic_id`
+async fn fetch_api<T: DeserializeOwned>(
+    http: &HttpClient,
+    endpoint: &str,
+    by: &str,
+    values: &[String],
+) -> Result<ApiResponse<T>, ApiError> {
+    let url = format!("{}?by={}&val={}", endpoint, by, values.join(","));
+
+    let response = http
+        .get_budgeted(&url, None, values.len() as u32)
+        .await?
+        .error_for_status()?;
+
+    Ok(response.json::<ApiResponse<T>>().await?)
+}
+
+async fn fetch_topic_data(
+    http: &HttpClient,
+    by: &str,
+    values: &[String],
+) -> Result<ApiResponse<TopicData>, ApiError> {
+    fetch_api(http, API_TOR_TOPIC_DATA_URL, by, values).await
+}
+
 // --- ОСНОВНАЯ ФУНКЦИЯ API (get_peer_stats) ---
+
+/// Обновляет статистику торрентов и возвращает результат поиска для каждого хеша.
+///
+/// Существование раздачи проверяется через get_tor_topic_data, затем для найденных
+/// раздач запрашиваются сиды, личи и время последнего сида через get_peer_stats.
+/// Порции по `limit` хешей запрашиваются параллельно (не больше `http.parallelism()`
+/// одновременно) в рамках общего бюджета ограничителя частоты.
 pub async fn get_api_peer_stats_by_hash_async(
+    http: &HttpClient,
     my_torrents: &mut [torrent::Torrent],
     limit: usize,
-) -> Result<Vec<String>> {
-    let client = reqwest::Client::new();
+) -> HashMap<String, HashLookup> {
+    log::debug!(
+        "Обновление статистики порциями по {} торрентов (параллельно до {})...",
+        limit,
+        http.parallelism()
+    );
+
+    // `buffered` сохраняет порядок порций, поэтому результат не зависит от порядка ответов
+    // Futures собираются заранее: замыкание внутри потока мешает выводу `Send` в async_trait
+    let requests: Vec<_> = my_torrents
+        .chunks_mut(limit)
+        .map(|chunk| process_hash_chunk(http, chunk))
+        .collect();
+    let chunk_results: Vec<Vec<(String, HashLookup)>> = stream::iter(requests)
+        .buffered(http.parallelism())
+        .collect()
+        .await;
+
+    let lookups: HashMap<String, HashLookup> = chunk_results.into_iter().flatten().collect();
+
+    let found: Vec<String> = my_torrents
+        .iter()
+        .filter(|t| lookups.get(&t.torrent_hash) == Some(&HashLookup::Found))
+        .map(|t| t.torrent_hash.clone())
+        .collect();
+    let peer_stats = get_api_peer_stats_async(http, &found, limit).await;
+
+    for torrent in my_torrents.iter_mut() {
+        match peer_stats.get(&torrent.torrent_hash) {
+            Some(Ok(Some(stats))) => {
+                torrent.seeders = stats.seeders;
+                torrent.leechers = stats.leechers;
+                if let Some(info) = torrent.topic_info.as_mut() {
+                    info.seeder_last_seen = stats.seeder_last_seen;
+                }
+            }
+            // Сиды из get_tor_topic_data остаются, личи неизвестны
+            Some(Ok(None)) | Some(Err(_)) => log::debug!(
+                "Нет статистики пиров для {} ({})",
+                torrent.torrent_hash,
+                torrent.name
+            ),
+            None => {}
+        }
+    }
+
+    lookups
+}
+
+/// Статистика пиров по хешу: `Ok(None)` — API вернул `null`
+pub type PeerStatsByHash = Result<Option<PeerStats>, ApiError>;
+
+/// Запрашивает статистику пиров по хешам (ключ результата — хеш в нижнем регистре)
+pub async fn get_api_peer_stats_async(
+    http: &HttpClient,
+    hashes: &[String],
+    limit: usize,
+) -> HashMap<String, PeerStatsByHash> {
+    let requests: Vec<_> = hashes
+        .chunks(limit)
+        .map(|chunk| process_peer_stats_chunk(http, chunk))
+        .collect();
+    let chunk_results: Vec<Vec<(String, PeerStatsByHash)>> = stream::iter(requests)
+        .buffered(http.parallelism())
+        .collect()
+        .await;
+
+    chunk_results.into_iter().flatten().collect()
+}
+
+async fn process_peer_stats_chunk(
+    http: &HttpClient,
+    hash_chunk: &[String],
+) -> Vec<(String, PeerStatsByHash)> {
+    let mut answered: HashMap<String, Option<PeerStats>> = HashMap::new();
+
+    let error = match fetch_api(http, API_PEER_STATS_URL, "hash", hash_chunk).await {
+        Ok(ApiResponse::Success(response_data)) => {
+            for (hash, stats) in response_data.result {
+                answered.insert(hash.to_lowercase(), stats);
+            }
+            ApiError::Missing
+        }
+        Ok(ApiResponse::Error(error_data)) => ApiError::Api(error_data.error.text),
+        Err(e) => e,
+    };
+
+    if error != ApiError::Missing {
+        log::warn!("⚠️ Ошибка запроса статистики пиров: {}", error);
+    }
+
+    hash_chunk
+        .iter()
+        .map(|hash| match answered.remove(&hash.to_lowercase()) {
+            Some(stats) => (hash.clone(), Ok(stats)),
+            None => (hash.clone(), Err(error.clone())),
+        })
+        .collect()
+}
+
+/// Запрашивает статистику для одной порции, исключая хеши, которые API отвергает
+async fn process_hash_chunk(
+    http: &HttpClient,
+    torrent_chunk: &mut [torrent::Torrent],
+) -> Vec<(String, HashLookup)> {
     const MAX_ATTEMPTS_PER_CHUNK: u8 = 5;
-    let mut problematic_ids: Vec<String> = Vec::new();
-
-    log::debug!("Обновление статистики порциями по {} торрентов...", limit);
-
-    for torrent_chunk in my_torrents.chunks_mut(limit) {
-        let mut current_work_list: Vec<&mut torrent::Torrent> = torrent_chunk.iter_mut().collect();
-        let mut attempts = 0;
-
-        'retry_loop: while !current_work_list.is_empty() && attempts < MAX_ATTEMPTS_PER_CHUNK {
-            attempts += 1;
-
-            let hashes: Vec<&str> = current_work_list
-                .iter()
-                .map(|t| t.torrent_hash.as_str())
-                .collect();
-
-            let hash_string = hashes.join(",");
-            let url = format!("{}?by=hash&val={}", API_TOR_TOPIC_DATA_URL, hash_string);
-
-            match client.get(&url).send().await {
-                Ok(response) => {
-                    match response.json::<ApiResponse>().await {
-                        Ok(ApiResponse::Success(response_data)) => {
-                            let mut hash_to_stats: HashMap<String, TopicData> = HashMap::new();
-                            for (_, maybe_data) in response_data.result {
-                                if let Some(data) = maybe_data {
-                                    // Приводим хэш из API к нижнему регистру один раз
-                                    hash_to_stats.insert(data.info_hash.to_lowercase(), data);
-                                }
-                            }
-
-                            for torrent in current_work_list.iter_mut() {
-                                // Ищем без аллокаций, т.к. torrent.torrent_hash уже в нижнем регистре
-                                if let Some(stats) = hash_to_stats.get(&torrent.torrent_hash) {
-                                    torrent.seeders = stats.seeders;
-                                    torrent.leechers = 0;
-                                } else {
-                                    log::warn!(
-                                        "⚠️ Хэш не найден на Rutracker: {} (Торрент: {})",
-                                        torrent.torrent_hash,
-                                        torrent.name
-                                    );
-                                    if !torrent.torrent_id.is_empty() {
-                                        problematic_ids.push(torrent.torrent_id.clone());
-                                    }
-                                }
-                            }
-                            break 'retry_loop;
-                        }
-                        Ok(ApiResponse::Error(error_data)) => {
-                            log::warn!("⚠️ Ошибка API Rutracker: {}", error_data.error.text);
-                            if let Some(bad_hash) = extract_invalid_hash(&error_data.error.text) {
-                                if let Some(index) = current_work_list
-                                    .iter()
-                                    .position(|t| t.torrent_hash.eq_ignore_ascii_case(bad_hash))
-                                {
-                                    let removed_torrent = current_work_list.remove(index);
-                                    if !removed_torrent.torrent_id.is_empty() {
-                                        problematic_ids.push(removed_torrent.torrent_id.clone());
-                                    }
-                                } else {
-                                    break 'retry_loop;
-                                }
-                            } else {
-                                break 'retry_loop;
-                            }
-                        }
-                        Err(e) => {
-                            log::error!("❌ Ошибка десериализации JSON: {}", e);
-                            break 'retry_loop;
-                        }
+    let mut lookups: Vec<(String, HashLookup)> = Vec::new();
+    let mut current_work_list: Vec<&mut torrent::Torrent> = torrent_chunk.iter_mut().collect();
+    let mut attempts = 0;
+
+    while !current_work_list.is_empty() {
+        if attempts >= MAX_ATTEMPTS_PER_CHUNK {
+            let error = ApiError::Api("превышено число попыток для порции".to_string());
+            for torrent in current_work_list.drain(..) {
+                lookups.push((
+                    torrent.torrent_hash.clone(),
+                    HashLookup::Failed(error.clone()),
+                ));
+            }
+            break;
+        }
+        attempts += 1;
+
+        let hashes: Vec<String> = current_work_list
+            .iter()
+            .map(|t| t.torrent_hash.clone())
+            .collect();
+
+        let error = match fetch_topic_data(http, "hash", &hashes).await {
+            Ok(ApiResponse::Success(response_data)) => {
+                let mut hash_to_stats: HashMap<String, TopicData> = HashMap::new();
+                for (_, maybe_data) in response_data.result {
+                    if let Some(data) = maybe_data {
+                        // Приводим хэш из API к нижнему регистру один раз
+                        hash_to_stats.insert(data.info_hash.to_lowercase(), data);
                     }
                 }
-                Err(e) => {
-                    log::error!("❌ Ошибка HTTP запроса: {}", e);
-                    break 'retry_loop;
+
+                for torrent in current_work_list.drain(..) {
+                    // Ищем без аллокаций, т.к. torrent.torrent_hash уже в нижнем регистре
+                    let lookup = if let Some(stats) = hash_to_stats.get(&torrent.torrent_hash) {
+                        torrent.seeders = stats.seeders;
+                        torrent.topic_info = Some(stats.info.clone());
+                        HashLookup::Found
+                    } else {
+                        log::warn!(
+                            "⚠️ Хэш не найден на Rutracker: {} (Торрент: {})",
+                            torrent.torrent_hash,
+                            torrent.name
+                        );
+                        HashLookup::NotFound
+                    };
+                    lookups.push((torrent.torrent_hash.clone(), lookup));
+                }
+                break;
+            }
+            Ok(ApiResponse::Error(error_data)) => {
+                log::warn!("⚠️ Ошибка API Rutracker: {}", error_data.error.text);
+                // API отвергает всю порцию из-за одного некорректного хеша:
+                // исключаем его и повторяем запрос для остальных
+                let bad_index = extract_invalid_hash(&error_data.error.text).and_then(|bad| {
+                    current_work_list
+                        .iter()
+                        .position(|t| t.torrent_hash.eq_ignore_ascii_case(bad))
+                });
+                match bad_index {
+                    Some(index) => {
+                        let removed_torrent = current_work_list.remove(index);
+                        lookups.push((removed_torrent.torrent_hash.clone(), HashLookup::NotFound));
+                        continue;
+                    }
+                    None => ApiError::Api(error_data.error.text),
                 }
             }
+            Err(e) => {
+                log::error!("❌ Ошибка запроса статистики: {}", e);
+                e
+            }
+        };
+
+        for torrent in current_work_list.drain(..) {
+            lookups.push((
+                torrent.torrent_hash.clone(),
+                HashLookup::Failed(error.clone()),
+            ));
         }
     }
 
-    Ok(problematic_ids)
+    lookups
 }
 
-pub async fn get_api_torrent_hash_by_id_async<T>(
-    ids: &[T],
+/// Хеш раздачи по ID темы: `Ok(None)` — API вернул `null`
+pub type HashById = Result<Option<String>, ApiError>;
+
+/// Данные темы по ID: `Ok(None)` — API вернул `null`
+pub type TopicById = Result<Option<TopicData>, ApiError>;
+
+/// Запрашивает текущие хеши раздач по ID тем.
+///
+/// Для каждого запрошенного ID возвращается `Ok(Some(hash))`, `Ok(None)` (API вернул `null`,
+/// т.е. раздачи нет) или ошибка, если ответ для этого ID получить не удалось.
+pub async fn get_api_torrent_hash_by_id_async(
+    http: &HttpClient,
+    ids: &[TopicId],
     limit: usize,
-) -> Result<HashMap<String, Option<String>>>
-where
-    T: fmt::Display,
-{
-    let client = reqwest::Client::new();
-    let mut all_results: HashMap<String, Option<String>> = HashMap::new();
-
-    if ids.is_empty() {
-        return Ok(all_results);
-    }
+) -> HashMap<TopicId, HashById> {
+    get_api_topic_data_by_id_async(http, ids, limit)
+        .await
+        .into_iter()
+        .map(|(id, data)| (id, data.map(|d| d.map(|d| d.info_hash))))
+        .collect()
+}
 
-    for id_chunk in ids.chunks(limit) {
-        let id_string = id_chunk
-            .iter()
-            .map(|id| id.to_string())
-            .collect::<Vec<String>>()
-            .join(",");
+/// Запрашивает полные данные тем по ID (хеш, название, раздел, размер, статус...).
+///
+/// Для каждого запрошенного ID в результате есть запись, как и в
+/// [`get_api_torrent_hash_by_id_async`].
+pub async fn get_api_topic_data_by_id_async(
+    http: &HttpClient,
+    ids: &[TopicId],
+    limit: usize,
+) -> HashMap<TopicId, TopicById> {
+    let requests: Vec<_> = ids
+        .chunks(limit)
+        .map(|id_chunk| process_id_chunk(http, id_chunk))
+        .collect();
+    let chunk_results: Vec<Vec<(TopicId, TopicById)>> = stream::iter(requests)
+        .buffered(http.parallelism())
+        .collect()
+        .await;
+
+    chunk_results.into_iter().flatten().collect()
+}
 
-        let url = format!("{}?by=topic_id&val={}", API_TOR_TOPIC_DATA_URL, id_string);
+async fn process_id_chunk(http: &HttpClient, id_chunk: &[TopicId]) -> Vec<(TopicId, TopicById)> {
+    let values: Vec<String> = id_chunk.iter().map(|id| id.to_string()).collect();
+    let mut answered: HashMap<TopicId, Option<TopicData>> = HashMap::new();
 
-        match client.get(&url).send().await {
-            Ok(response) => match response.json::<ApiResponseTopicData>().await {
-                Ok(response_data) => {
-                    for (id, maybe_data) in response_data.result {
-                        all_results.insert(id, maybe_data.map(|d| d.info_hash));
+    let error = match fetch_topic_data(http, "topic_id", &values).await {
+        Ok(ApiResponse::Success(response_data)) => {
+            for (id, maybe_data) in response_data.result {
+                match id.parse::<TopicId>() {
+                    Ok(id) => {
+                        answered.insert(id, maybe_data);
                     }
+                    Err(e) => log::warn!("⚠️ API вернул некорректный ID темы '{}': {}", id, e),
                 }
-                Err(e) => log::error!("❌ Ошибка десериализации: {}", e),
-            },
-            Err(e) => log::error!("❌ Ошибка HTTP: {}", e),
+            }
+            ApiError::Missing
         }
+        Ok(ApiResponse::Error(error_data)) => ApiError::Api(error_data.error.text),
+        Err(e) => e,
+    };
+
+    if error != ApiError::Missing {
+        log::error!("❌ Ошибка запроса данных тем по ID: {}", error);
     }
 
-    Ok(all_results)
+    // ID, по которым ответа нет, никогда не считаются удаленными
+    id_chunk
+        .iter()
+        .map(|id| match answered.remove(id) {
+            Some(data) => (*id, Ok(data)),
+            None => (*id, Err(error.clone())),
+        })
+        .collect()
 }
 
 pub async fn download_torrent(
-    client: &Client,
+    http: &HttpClient,
     headers: &HeaderMap,
-    topic_id: u64,
+    topic_id: TopicId,
 ) -> Result<String> {
     let download_url = format!("https://rutracker.org/forum/dl.php?t={}", topic_id);
     let output_filename = format!("t{}.torrent", topic_id);
 
     log::info!("Попытка скачивания файла (тема {})...", topic_id);
 
-    let mut download_response = client
-        .get(&download_url)
-        .headers(headers.clone())
-        .send()
-        .await?;
-
-    let content_type = download_response
-        .headers()
-        .get(header::CONTENT_TYPE)
-        .map(|v| v.to_str().unwrap_or(""))
-        .unwrap_or("");
-
-    if download_response.status().is_success() && content_type.contains("application/x-bittorrent")
-    {
-        let mut output_file = tokio::fs::File::create(&output_filename).await?;
-
-        while let Some(chunk) = download_response.chunk().await? {
-            output_file.write_all(&chunk).await?;
-        }
-
-        Ok(output_filename)
-    } else {
-        log::error!("❌ Ошибка авторизации или скачивания.");
-        Err(anyhow::anyhow!(
-            "Ошибка скачивания: сервер не вернул .torrent файл (статус: {}).",
-            download_response.status()
-        ))
-    }
+    download_torrent_file(http, headers, &download_url, &output_filename).await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::topic_info::TorStatus;
 
     #[test]
     fn test_extract_torrent_id_from_comment() {
         assert_eq!(
             extract_torrent_id_from_comment("https://rutracker.org/forum/viewtopic.php?t=1234567"),
-            "1234567"
+            Ok(TopicId::new(1234567))
+        );
+        assert_eq!(
+            extract_torrent_id_from_comment("http://rutracker.nl/forum/dl.php?t=5#x"),
+            Ok(TopicId::new(5))
         );
         assert_eq!(
             extract_torrent_id_from_comment("Просто текст t=98765"),
-            "98765"
+            Err(TopicIdError::NotFound)
+        );
+        assert_eq!(
+            extract_torrent_id_from_comment("Без идентификатора"),
+            Err(TopicIdError::NotFound)
         );
-        assert_eq!(extract_torrent_id_from_comment("Без идентификатора"), "");
     }
 
     #[test]
@@ -273,4 +501,48 @@ mod tests {
         );
         assert_eq!(extract_invalid_hash("Some other random api error"), None);
     }
+
+    #[test]
+    fn test_topic_data_decodes_all_fields() {
+        let body = r#"{"result": {"2142": {"info_hash": "658EDAB6AF0B424E62FEFEC0E39DBE2AC55B9AE3",
+            "forum_id": 1379, "poster_id": 42, "size": 1048576, "reg_time": 1709251200,
+            "tor_status": 7, "seeders": 3, "topic_title": "Ubuntu", "seeder_last_seen": 1709337600,
+            "dl_count": 10}, "1": null}}"#;
+
+        let ApiResponse::Success(data) =
+            serde_json::from_str::<ApiResponse<TopicData>>(body).unwrap()
+        else {
+            panic!("Ожидался успешный ответ");
+        };
+        let topic = data.result["2142"].as_ref().unwrap();
+
+        assert_eq!(topic.seeders, 3);
+        assert_eq!(topic.info.forum_id, 1379);
+        assert_eq!(topic.info.topic_title, "Ubuntu");
+        assert_eq!(topic.info.tor_status, TorStatus::Absorbed);
+        assert_eq!(topic.info.seeder_last_seen, 1709337600);
+        assert!(data.result["1"].is_none());
+    }
+
+    #[test]
+    fn test_peer_stats_decode() {
+        let body = r#"{"result": {"658EDAB6AF0B424E62FEFEC0E39DBE2AC55B9AE3": [5, 2, 1709337600],
+            "0000000000000000000000000000000000000000": null}}"#;
+
+        let ApiResponse::Success(data) =
+            serde_json::from_str::<ApiResponse<PeerStats>>(body).unwrap()
+        else {
+            panic!("Ожидался успешный ответ");
+        };
+
+        assert_eq!(
+            data.result["658EDAB6AF0B424E62FEFEC0E39DBE2AC55B9AE3"],
+            Some(PeerStats {
+                seeders: 5,
+                leechers: 2,
+                seeder_last_seen: 1709337600
+            })
+        );
+        assert!(data.result["0000000000000000000000000000000000000000"].is_none());
+    }
 }
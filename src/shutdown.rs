@@ -0,0 +1,163 @@
+// src/shutdown.rs
+
+//!
+//! Корректная остановка по Ctrl+C / SIGTERM: новые действия не начинаются, текущее
+//! доводится до конца (или откатывается), отчет о частично выполненном запуске сохраняется
+//!
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::Notify;
+
+/// Флаг запрошенной остановки
+#[derive(Default)]
+pub struct Shutdown {
+    requested: AtomicBool,
+    notify: Notify,
+}
+
+impl Shutdown {
+    /// Создает флаг и начинает слушать сигналы остановки.
+    ///
+    /// Первый сигнал только выставляет флаг, повторный завершает процесс сразу.
+    pub fn listen() -> Arc<Self> {
+        let shutdown = Arc::new(Shutdown::default());
+        let handle = shutdown.clone();
+        tokio::spawn(async move {
+            if wait_signal().await.is_err() {
+                log::warn!("⚠️ Не удалось подписаться на сигналы остановки");
+                return;
+            }
+            log::warn!(
+                "⏹️ Получен сигнал остановки: новые действия не начинаются, текущее будет завершено. \
+                 Повторный сигнал прервет работу немедленно."
+            );
+            handle.request();
+
+            if wait_signal().await.is_ok() {
+                log::error!("⛔ Повторный сигнал остановки: работа прервана");
+                std::process::exit(130);
+            }
+        });
+        shutdown
+    }
+
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// Ждет запроса остановки (сразу возвращается, если он уже был)
+    pub async fn requested(&self) {
+        let notified = self.notify.notified();
+        if self.is_requested() {
+            return;
+        }
+        notified.await;
+    }
+}
+
+#[cfg(unix)]
+async fn wait_signal() -> std::io::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result,
+        _ = terminate.recv() => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_signal() -> std::io::Result<()> {
+    tokio::signal::ctrl_c().await
+}
+
+/// Временный файл, который удаляется, когда становится не нужен (в том числе при ошибке)
+pub struct TempFile(PathBuf);
+
+impl TempFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        TempFile(path.into())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.0) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::warn!(
+                    "⚠️ Не удалось удалить временный файл {}: {}",
+                    self.0.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// Удаляет из `dir` файлы старше `max_age`, оставшиеся после аварийно прерванных запусков
+pub fn remove_stale_files(dir: &Path, max_age: Duration) -> usize {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    let now = SystemTime::now();
+    let mut removed = 0;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let stale = entry
+            .metadata()
+            .ok()
+            .filter(|m| m.is_file())
+            .and_then(|m| m.modified().ok())
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age >= max_age);
+        if stale && std::fs::remove_file(&path).is_ok() {
+            log::debug!("Удален оставшийся временный файл {}", path.display());
+            removed += 1;
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_requested_after_request() {
+        let shutdown = Shutdown::default();
+        assert!(!shutdown.is_requested());
+        shutdown.request();
+        assert!(shutdown.is_requested());
+        // Уже запрошенная остановка не блокирует ожидание
+        shutdown.requested().await;
+    }
+
+    #[test]
+    fn test_temp_file_removed_on_drop() {
+        let dir = std::env::temp_dir().join("tracker-updater-shutdown-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("1.torrent");
+        std::fs::write(&path, b"d").unwrap();
+
+        drop(TempFile::new(&path));
+        assert!(!path.exists());
+
+        std::fs::write(&path, b"d").unwrap();
+        assert_eq!(remove_stale_files(&dir, Duration::from_secs(3600)), 0);
+        assert_eq!(remove_stale_files(&dir, Duration::ZERO), 1);
+        assert!(!path.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
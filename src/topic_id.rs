@@ -0,0 +1,177 @@
+// src/topic_id.rs
+
+//!
+//! Идентификатор темы на трекере и разбор ссылок на тему из комментария торрента
+//!
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Числовой ID темы (раздачи) на трекере
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TopicId(u64);
+
+impl TopicId {
+    pub const fn new(id: u64) -> Self {
+        TopicId(id)
+    }
+
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for TopicId {
+    fn from(id: u64) -> Self {
+        TopicId(id)
+    }
+}
+
+impl fmt::Display for TopicId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for TopicId {
+    type Err = TopicIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(TopicIdError::Empty);
+        }
+        if !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(TopicIdError::NotNumeric(s.to_string()));
+        }
+        s.parse::<u64>()
+            .map(TopicId)
+            .map_err(|_| TopicIdError::NotNumeric(s.to_string()))
+    }
+}
+
+/// Причина, по которой из комментария не удалось получить ID темы
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TopicIdError {
+    #[error("в комментарии нет ссылки на тему трекера")]
+    NotFound,
+    #[error("в ссылке на тему пустой ID")]
+    Empty,
+    #[error("ID темы '{0}' не является числом")]
+    NotNumeric(String),
+}
+
+/// Описание ссылок на тему конкретного трекера
+pub struct TopicUrlPattern {
+    /// Домены трекера (поддомены, например `www.`, тоже принимаются)
+    pub domains: &'static [&'static str],
+    /// Имена скриптов, в параметрах которых передается ID темы
+    pub scripts: &'static [&'static str],
+    /// Имя параметра запроса с ID темы
+    pub param: &'static str,
+}
+
+impl TopicUrlPattern {
+    fn matches_host(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        self.domains.iter().any(|domain| {
+            host == *domain
+                || host
+                    .strip_suffix(domain)
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        })
+    }
+
+    fn matches_script(&self, url: &Url) -> bool {
+        url.path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .is_some_and(|script| self.scripts.contains(&script))
+    }
+}
+
+/// Ищет в комментарии ссылку на тему трекера и возвращает ID темы.
+///
+/// Берется последняя ссылка, подходящая под `pattern` и содержащая корректный ID.
+/// Если подходящие ссылки есть, но ID в них некорректен, возвращается причина ошибки.
+pub fn find_topic_id(comment: &str, pattern: &TopicUrlPattern) -> Result<TopicId, TopicIdError> {
+    let mut found = None;
+    let mut last_error = TopicIdError::NotFound;
+
+    let candidates = comment
+        .split(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | '(' | ')'))
+        .filter(|token| !token.is_empty());
+
+    for token in candidates {
+        let Some(url) = parse_url(token) else {
+            continue;
+        };
+
+        if !url.host_str().is_some_and(|h| pattern.matches_host(h)) || !pattern.matches_script(&url)
+        {
+            continue;
+        }
+
+        let Some((_, value)) = url.query_pairs().find(|(key, _)| key == pattern.param) else {
+            continue;
+        };
+
+        match value.parse::<TopicId>() {
+            Ok(id) => found = Some(id),
+            Err(e) => last_error = e,
+        }
+    }
+
+    found.ok_or(last_error)
+}
+
+/// Разбирает токен как URL. Ссылки без схемы (`rutracker.org/forum/...`) дополняются `https://`.
+fn parse_url(token: &str) -> Option<Url> {
+    let token = token.trim_end_matches(['.', ',', ';']);
+    if token.starts_with("http://") || token.starts_with("https://") {
+        Url::parse(token).ok()
+    } else if token.contains('/') && token.contains('?') {
+        Url::parse(&format!("https://{}", token)).ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATTERN: TopicUrlPattern = TopicUrlPattern {
+        domains: &["example.org"],
+        scripts: &["viewtopic.php"],
+        param: "t",
+    };
+
+    #[test]
+    fn test_topic_id_from_str() {
+        assert_eq!("123".parse::<TopicId>(), Ok(TopicId::new(123)));
+        assert_eq!("".parse::<TopicId>(), Err(TopicIdError::Empty));
+        assert_eq!(
+            "12a".parse::<TopicId>(),
+            Err(TopicIdError::NotNumeric("12a".into()))
+        );
+        assert_eq!(
+            "-5".parse::<TopicId>(),
+            Err(TopicIdError::NotNumeric("-5".into()))
+        );
+    }
+
+    #[test]
+    fn test_find_topic_id_subdomain_and_foreign_host() {
+        assert_eq!(
+            find_topic_id("https://www.example.org/viewtopic.php?t=7", &PATTERN),
+            Ok(TopicId::new(7))
+        );
+        assert_eq!(
+            find_topic_id("https://notexample.org/viewtopic.php?t=7", &PATTERN),
+            Err(TopicIdError::NotFound)
+        );
+    }
+}
@@ -0,0 +1,234 @@
+// src/topic_info.rs
+
+//!
+//! Метаданные темы на трекере (раздел, автор, размер, статус, название и т.д.)
+//!
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Статус раздачи (`tor_status` в API Rutracker)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "u8", into = "u8")]
+pub enum TorStatus {
+    NotVerified,
+    Closed,
+    Verified,
+    NeedsEdit,
+    NotFormatted,
+    Duplicate,
+    /// Закрыто правообладателем
+    ClosedByRightholder,
+    Absorbed,
+    Doubtful,
+    Checking,
+    Temporary,
+    Premoderation,
+    Other(u8),
+}
+
+impl From<u8> for TorStatus {
+    fn from(code: u8) -> Self {
+        match code {
+            0 => TorStatus::NotVerified,
+            1 => TorStatus::Closed,
+            2 => TorStatus::Verified,
+            3 => TorStatus::NeedsEdit,
+            4 => TorStatus::NotFormatted,
+            5 => TorStatus::Duplicate,
+            6 => TorStatus::ClosedByRightholder,
+            7 => TorStatus::Absorbed,
+            8 => TorStatus::Doubtful,
+            9 => TorStatus::Checking,
+            10 => TorStatus::Temporary,
+            11 => TorStatus::Premoderation,
+            other => TorStatus::Other(other),
+        }
+    }
+}
+
+impl From<TorStatus> for u8 {
+    fn from(status: TorStatus) -> Self {
+        match status {
+            TorStatus::NotVerified => 0,
+            TorStatus::Closed => 1,
+            TorStatus::Verified => 2,
+            TorStatus::NeedsEdit => 3,
+            TorStatus::NotFormatted => 4,
+            TorStatus::Duplicate => 5,
+            TorStatus::ClosedByRightholder => 6,
+            TorStatus::Absorbed => 7,
+            TorStatus::Doubtful => 8,
+            TorStatus::Checking => 9,
+            TorStatus::Temporary => 10,
+            TorStatus::Premoderation => 11,
+            TorStatus::Other(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for TorStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TorStatus::NotVerified => write!(f, "не проверено"),
+            TorStatus::Closed => write!(f, "закрыто"),
+            TorStatus::Verified => write!(f, "проверено"),
+            TorStatus::NeedsEdit => write!(f, "недооформлено"),
+            TorStatus::NotFormatted => write!(f, "не оформлено"),
+            TorStatus::Duplicate => write!(f, "повтор"),
+            TorStatus::ClosedByRightholder => write!(f, "закрыто правообладателем"),
+            TorStatus::Absorbed => write!(f, "поглощено"),
+            TorStatus::Doubtful => write!(f, "сомнительно"),
+            TorStatus::Checking => write!(f, "проверяется"),
+            TorStatus::Temporary => write!(f, "временная"),
+            TorStatus::Premoderation => write!(f, "премодерация"),
+            TorStatus::Other(code) => write!(f, "статус {}", code),
+        }
+    }
+}
+
+impl FromStr for TorStatus {
+    type Err = String;
+
+    /// Имя статуса в конфигурации (`closed`, `duplicate`, `absorbed`, ...) или его код
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let status = match s.trim() {
+            "not_verified" => TorStatus::NotVerified,
+            "closed" => TorStatus::Closed,
+            "verified" => TorStatus::Verified,
+            "needs_edit" => TorStatus::NeedsEdit,
+            "not_formatted" => TorStatus::NotFormatted,
+            "duplicate" => TorStatus::Duplicate,
+            "closed_by_rightholder" => TorStatus::ClosedByRightholder,
+            "absorbed" => TorStatus::Absorbed,
+            "doubtful" => TorStatus::Doubtful,
+            "checking" => TorStatus::Checking,
+            "temporary" => TorStatus::Temporary,
+            "premoderation" => TorStatus::Premoderation,
+            other => other
+                .parse::<u8>()
+                .map(TorStatus::from)
+                .map_err(|_| format!("неизвестный статус раздачи '{}'", other))?,
+        };
+        Ok(status)
+    }
+}
+
+/// Сведения о теме, полученные от трекера
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopicInfo {
+    pub topic_title: String,
+    pub forum_id: u32,
+    #[serde(default)]
+    pub poster_id: u64,
+    /// Размер раздачи в байтах
+    pub size: u64,
+    /// Время регистрации раздачи (unix time)
+    pub reg_time: i64,
+    pub tor_status: TorStatus,
+    /// Когда на трекере в последний раз видели сида (unix time, 0 — неизвестно)
+    #[serde(default)]
+    pub seeder_last_seen: i64,
+}
+
+impl TopicInfo {
+    /// Описания отличий `new` от `self`: переименование, перенос в другой раздел, смена размера
+    pub fn changes_to(&self, new: &TopicInfo) -> Vec<String> {
+        let mut changes = Vec::new();
+
+        if self.topic_title != new.topic_title {
+            changes.push(format!(
+                "название: '{}' → '{}'",
+                self.topic_title, new.topic_title
+            ));
+        }
+        if self.forum_id != new.forum_id {
+            changes.push(format!("раздел: {} → {}", self.forum_id, new.forum_id));
+        }
+        if self.size != new.size {
+            changes.push(format!(
+                "размер: {} MB → {} MB",
+                self.size / (1024 * 1024),
+                new.size / (1024 * 1024)
+            ));
+        }
+        if self.tor_status != new.tor_status {
+            changes.push(format!("статус: {} → {}", self.tor_status, new.tor_status));
+        }
+
+        changes
+    }
+}
+
+/// Дата в формате `ГГГГ-ММ-ДД` для unix time (UTC)
+pub fn format_unix_date(timestamp: i64) -> String {
+    // Алгоритм days-from-civil в обратную сторону (H. Hinnant)
+    let days = timestamp.div_euclid(86_400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info() -> TopicInfo {
+        TopicInfo {
+            topic_title: "Ubuntu 24.04".into(),
+            forum_id: 10,
+            poster_id: 1,
+            size: 10 * 1024 * 1024,
+            reg_time: 0,
+            tor_status: TorStatus::Verified,
+            seeder_last_seen: 0,
+        }
+    }
+
+    #[test]
+    fn test_tor_status_roundtrip() {
+        for code in 0..=12u8 {
+            assert_eq!(u8::from(TorStatus::from(code)), code);
+        }
+        assert_eq!(TorStatus::from(5), TorStatus::Duplicate);
+        assert_eq!(TorStatus::from(6), TorStatus::ClosedByRightholder);
+        assert_eq!(
+            "closed_by_rightholder".parse(),
+            Ok(TorStatus::ClosedByRightholder)
+        );
+        assert_eq!("absorbed".parse(), Ok(TorStatus::Absorbed));
+        assert_eq!("8".parse(), Ok(TorStatus::Doubtful));
+        assert!("unknown".parse::<TorStatus>().is_err());
+    }
+
+    #[test]
+    fn test_changes_to() {
+        let old = info();
+        assert!(old.changes_to(&old).is_empty());
+
+        let new = TopicInfo {
+            topic_title: "Ubuntu 24.04.1".into(),
+            forum_id: 11,
+            ..info()
+        };
+        let changes = old.changes_to(&new);
+        assert_eq!(changes.len(), 2);
+        assert!(changes[0].starts_with("название"));
+        assert_eq!(changes[1], "раздел: 10 → 11");
+    }
+
+    #[test]
+    fn test_format_unix_date() {
+        assert_eq!(format_unix_date(0), "1970-01-01");
+        assert_eq!(format_unix_date(1_709_251_200), "2024-03-01");
+    }
+}
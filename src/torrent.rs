@@ -1,10 +1,14 @@
 // src/torrent.rs
+use crate::topic_id::TopicId;
+use crate::topic_info::{format_unix_date, TopicInfo};
+
 // Структура для представления торрента
 #[derive(Clone, PartialEq, Eq)]
 pub struct Torrent {
     pub name: String,
     pub torrent_hash: String,
-    pub torrent_id: String,
+    /// ID темы на трекере (None, если его не удалось извлечь из комментария)
+    pub torrent_id: Option<TopicId>,
     pub tracker: String,
     pub comment: String,
     pub state: String,
@@ -14,9 +18,27 @@ pub struct Torrent {
     pub seeders: u32,
     pub leechers: u32,
     pub save_path: String,
+    /// Сведения о теме с трекера (последние известные)
+    pub topic_info: Option<TopicInfo>,
 }
 use std::fmt;
 
+impl Torrent {
+    /// ID темы в виде строки для вывода (пустая строка, если ID нет)
+    pub fn topic_id_str(&self) -> String {
+        self.torrent_id.map(|id| id.to_string()).unwrap_or_default()
+    }
+
+    /// Сколько полных дней на трекере нет сидов (`None`, если сиды есть или это неизвестно)
+    pub fn days_without_seeders(&self, now: i64) -> Option<u64> {
+        if self.seeders > 0 {
+            return None;
+        }
+        let last_seen = self.topic_info.as_ref()?.seeder_last_seen;
+        (last_seen > 0).then(|| ((now - last_seen).max(0) / 86_400) as u64)
+    }
+}
+
 // Реализация Debug для компактного однострочного вывода
 impl fmt::Debug for Torrent {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -30,7 +52,7 @@ impl fmt::Debug for Torrent {
 
         debug_struct
             .field("name", &self.name)
-            .field("id", &self.torrent_id)
+            .field("id", &self.topic_id_str())
             .field("hash", &self.torrent_hash)
             .field("state", &self.state)
             .field("peers", &peers)
@@ -58,7 +80,7 @@ impl fmt::Display for Torrent {
             f,
             "Имя: {}\n  - ID: {}\n  - Хеш: {}\n  - Статус: {}\n  - Категория: {}\n  - Размер: {} MB\n  - Сиды: {} | Личи: {}",
             self.name,
-            self.torrent_id,
+            self.topic_id_str(),
             self.torrent_hash,
             self.state,
             self.category,
@@ -82,6 +104,27 @@ impl fmt::Display for Torrent {
             write!(f, "\n  - Путь: {}", self.save_path)?;
         }
 
+        // Добавляем сведения о теме, если трекер их вернул
+        if let Some(info) = &self.topic_info {
+            write!(
+                f,
+                "\n  - Тема: {}\n  - Раздел: {} | Автор: {} | Статус раздачи: {}\n  - Размер на трекере: {} MB | Зарегистрирован: {}",
+                info.topic_title,
+                info.forum_id,
+                info.poster_id,
+                info.tor_status,
+                info.size / (1024 * 1024),
+                format_unix_date(info.reg_time)
+            )?;
+            if info.seeder_last_seen > 0 {
+                write!(
+                    f,
+                    "\n  - Сид последний раз: {}",
+                    format_unix_date(info.seeder_last_seen)
+                )?;
+            }
+        }
+
         // Добавляем комментарий, если он есть
         if !self.comment.is_empty() {
             write!(f, "\n  - Комментарий: {}", self.comment)?;
@@ -101,7 +144,7 @@ mod tests {
         let t = Torrent {
             name: "Test Ubuntu".into(),
             torrent_hash: "ABCDEF".into(),
-            torrent_id: "123".into(),
+            torrent_id: Some(TopicId::new(123)),
             tracker: "rutracker".into(),
             comment: "".into(),
             state: "paused".into(),
@@ -111,6 +154,7 @@ mod tests {
             seeders: 15,
             leechers: 3,
             save_path: "/downloads".into(),
+            topic_info: None,
         };
         let display_output = format!("{}", t);
 
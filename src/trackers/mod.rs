@@ -0,0 +1,135 @@
+// src/trackers/mod.rs
+
+//!
+//! Абстракция над трекерами: распознавание торрента, ID темы, проверка хеша и скачивание .torrent
+//!
+
+pub mod nnmclub;
+pub mod rutracker;
+
+use crate::http::HttpClient;
+use crate::topic_id::{TopicId, TopicIdError};
+use crate::topic_info::TopicInfo;
+use crate::torrent::Torrent;
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::header::{self, HeaderMap};
+use std::collections::HashMap;
+use tokio::io::AsyncWriteExt;
+
+pub use nnmclub::NnmClubProvider;
+pub use rutracker::RutrackerProvider;
+
+/// Результат проверки одного торрента на трекере
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckOutcome {
+    /// Хеш совпадает с раздачей на трекере
+    Current,
+    /// Раздача обновлена, у нее новый хеш (и, если трекер их отдает, новые сведения о теме)
+    Changed {
+        new_hash: String,
+        topic: Option<TopicInfo>,
+    },
+    /// Трекер явно подтвердил, что раздачи больше нет
+    Removed,
+    /// Проверить не удалось — никаких действий с таким торрентом не выполняется
+    Unknown { error: String },
+}
+
+impl std::fmt::Display for CheckOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckOutcome::Current => write!(f, "раздача актуальна"),
+            CheckOutcome::Changed { new_hash, .. } => {
+                write!(f, "раздача обновлена (новый хеш {})", new_hash)
+            }
+            CheckOutcome::Removed => write!(f, "раздача удалена с трекера"),
+            CheckOutcome::Unknown { error } => write!(f, "не удалось проверить: {}", error),
+        }
+    }
+}
+
+/// Трекер, торренты с которого утилита умеет поддерживать в актуальном состоянии
+#[async_trait]
+pub trait TrackerProvider: Send + Sync {
+    /// Короткое имя трекера для логов и отчетов
+    fn name(&self) -> &'static str;
+
+    /// Относится ли торрент к этому трекеру (по URL трекера из qBittorrent)
+    fn recognizes(&self, tracker_url: &str) -> bool;
+
+    /// Заполняет ли `check_torrents` число сидов и личей в торрентах
+    fn reports_peer_stats(&self) -> bool {
+        false
+    }
+
+    /// Извлекает ID темы из комментария торрента
+    fn extract_topic_id(&self, comment: &str) -> Result<TopicId, TopicIdError>;
+
+    /// Проверяет актуальность торрентов на трекере.
+    ///
+    /// Попутно обновляет статистику в `torrents`. Возвращает результат проверки
+    /// для каждого торрента, ключ — хеш торрента в qBittorrent.
+    async fn check_torrents(&self, torrents: &mut [Torrent]) -> HashMap<String, CheckOutcome>;
+
+    /// Скачивает .torrent файл темы и возвращает путь к нему
+    async fn download_metainfo(&self, topic_id: TopicId) -> Result<String>;
+}
+
+/// Находит трекер, к которому относится торрент
+pub fn find_provider<'a>(
+    providers: &'a [Box<dyn TrackerProvider>],
+    tracker_url: &str,
+) -> Option<&'a dyn TrackerProvider> {
+    providers
+        .iter()
+        .find(|p| p.recognizes(tracker_url))
+        .map(|p| p.as_ref())
+}
+
+/// Скачивает .torrent файл по ссылке и сохраняет его под именем `output_filename`
+/// во временной папке (`paths::download_dir`). Возвращает полный путь к файлу.
+pub(crate) async fn download_torrent_file(
+    http: &HttpClient,
+    headers: &HeaderMap,
+    download_url: &str,
+    output_filename: &str,
+) -> Result<String> {
+    let mut download_response = http.get(download_url, Some(headers)).await?;
+
+    let content_type = download_response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|v| v.to_str().unwrap_or(""))
+        .unwrap_or("");
+
+    if download_response.status().is_success() && content_type.contains("application/x-bittorrent")
+    {
+        let dir = crate::paths::download_dir();
+        tokio::fs::create_dir_all(&dir).await?;
+        let output_path = dir.join(output_filename);
+        let mut output_file = tokio::fs::File::create(&output_path).await?;
+
+        // Недокачанный файл не оставляем
+        let written: Result<()> = async {
+            while let Some(chunk) = download_response.chunk().await? {
+                output_file.write_all(&chunk).await?;
+            }
+            Ok(())
+        }
+        .await;
+        if let Err(e) = written {
+            drop(output_file);
+            let _ = tokio::fs::remove_file(&output_path).await;
+            return Err(e);
+        }
+
+        Ok(output_path.to_string_lossy().into_owned())
+    } else {
+        log::error!("❌ Ошибка авторизации или скачивания.");
+        Err(anyhow::anyhow!(
+            "Ошибка скачивания: сервер не вернул .torrent файл (статус: {}).",
+            download_response.status()
+        ))
+    }
+}
@@ -0,0 +1,186 @@
+// src/trackers/nnmclub.rs
+
+use super::{download_torrent_file, CheckOutcome, TrackerProvider};
+use crate::http::HttpClient;
+use crate::topic_id::{find_topic_id, TopicId, TopicIdError, TopicUrlPattern};
+use crate::torrent::Torrent;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderValue, COOKIE};
+use std::collections::HashMap;
+
+const NNMCLUB_FORUM_URL: &str = "https://nnmclub.to/forum";
+
+/// Ссылки на темы NNM-Club: `viewtopic.php?t=ID` на всех зеркалах
+const NNMCLUB_TOPIC_URL: TopicUrlPattern = TopicUrlPattern {
+    domains: &["nnmclub.to", "nnm-club.me", "nnm-club.ws", "nnm-club.name"],
+    scripts: &["viewtopic.php"],
+    param: "t",
+};
+
+/// Тексты, которыми форум сообщает об отсутствии темы
+const TOPIC_NOT_FOUND_MARKERS: &[&str] = &["Тема не найдена", "Такой темы не существует"];
+
+/// Признаки страницы форума: без них сообщение об отсутствии темы не принимается
+/// (заглушка прокси или зеркала может содержать тот же текст)
+const FORUM_LAYOUT_MARKERS: &[&str] = &[":: NNM-Club</title>", "class=\"forumline\""];
+
+/// Результат разбора страницы темы
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopicPage {
+    /// Тема существует
+    Active {
+        /// Хеш раздачи из magnet-ссылки (в нижнем регистре)
+        info_hash: String,
+        /// ID вложения для download.php (виден только авторизованным пользователям)
+        download_id: Option<u64>,
+    },
+    /// Форум явно сообщил, что темы нет (на странице с разметкой форума)
+    NotFound,
+}
+
+/// Разбирает HTML страницы темы NNM-Club
+pub fn parse_topic_page(html: &str) -> Result<TopicPage> {
+    if let Some(info_hash) = extract_magnet_hash(html) {
+        return Ok(TopicPage::Active {
+            info_hash,
+            download_id: extract_download_id(html),
+        });
+    }
+
+    if TOPIC_NOT_FOUND_MARKERS
+        .iter()
+        .any(|marker| html.contains(marker))
+        && FORUM_LAYOUT_MARKERS
+            .iter()
+            .all(|marker| html.contains(marker))
+    {
+        return Ok(TopicPage::NotFound);
+    }
+
+    Err(anyhow::anyhow!(
+        "На странице темы нет magnet-ссылки и сообщения об удалении темы"
+    ))
+}
+
+fn extract_magnet_hash(html: &str) -> Option<String> {
+    let (_, rest) = html.split_once("urn:btih:")?;
+    let hash: String = rest
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect();
+
+    (hash.len() == 40 && hash.chars().all(|c| c.is_ascii_hexdigit())).then(|| hash.to_lowercase())
+}
+
+fn extract_download_id(html: &str) -> Option<u64> {
+    let (_, rest) = html.split_once("download.php?id=")?;
+    let id: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    id.parse().ok()
+}
+
+/// NNM-Club: проверка по странице темы (magnet-ссылка), скачивание через download.php
+pub struct NnmClubProvider {
+    http: HttpClient,
+    headers: HeaderMap,
+}
+
+impl NnmClubProvider {
+    /// `cookie` — строка cookie авторизованной сессии форума (`phpbb2mysql_4_sid=...; ...`)
+    pub fn new(http: HttpClient, cookie: &str) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        headers.insert(COOKIE, HeaderValue::from_str(cookie)?);
+
+        Ok(NnmClubProvider { http, headers })
+    }
+
+    async fn fetch_topic_page(&self, topic_id: TopicId) -> Result<TopicPage> {
+        let url = format!("{}/viewtopic.php?t={}", NNMCLUB_FORUM_URL, topic_id);
+        let html = self
+            .http
+            .get_budgeted(&url, Some(&self.headers), 1)
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        parse_topic_page(&html).with_context(|| format!("Тема NNM-Club {}", topic_id))
+    }
+}
+
+#[async_trait]
+impl TrackerProvider for NnmClubProvider {
+    fn name(&self) -> &'static str {
+        "NNM-Club"
+    }
+
+    fn recognizes(&self, tracker_url: &str) -> bool {
+        tracker_url.contains("nnm-club") || tracker_url.contains("nnmclub")
+    }
+
+    fn extract_topic_id(&self, comment: &str) -> Result<TopicId, TopicIdError> {
+        find_topic_id(comment, &NNMCLUB_TOPIC_URL)
+    }
+
+    async fn check_torrents(&self, torrents: &mut [Torrent]) -> HashMap<String, CheckOutcome> {
+        let mut outcomes = HashMap::new();
+
+        for torrent in torrents.iter() {
+            let outcome = match torrent.torrent_id {
+                None => CheckOutcome::Unknown {
+                    error: "ID темы неизвестен".to_string(),
+                },
+                Some(topic_id) => match self.fetch_topic_page(topic_id).await {
+                    Ok(TopicPage::Active { info_hash, .. })
+                        if info_hash == torrent.torrent_hash =>
+                    {
+                        CheckOutcome::Current
+                    }
+                    Ok(TopicPage::Active { info_hash, .. }) => CheckOutcome::Changed {
+                        new_hash: info_hash,
+                        topic: None,
+                    },
+                    Ok(TopicPage::NotFound) => CheckOutcome::Removed,
+                    Err(e) => {
+                        log::error!("❌ Ошибка проверки темы NNM-Club {}: {:#}", topic_id, e);
+                        CheckOutcome::Unknown {
+                            error: format!("{:#}", e),
+                        }
+                    }
+                },
+            };
+            outcomes.insert(torrent.torrent_hash.clone(), outcome);
+        }
+
+        outcomes
+    }
+
+    async fn download_metainfo(&self, topic_id: TopicId) -> Result<String> {
+        let download_id = match self.fetch_topic_page(topic_id).await? {
+            TopicPage::Active {
+                download_id: Some(id),
+                ..
+            } => id,
+            TopicPage::Active { .. } => {
+                return Err(anyhow::anyhow!(
+                    "На странице темы {} нет ссылки на .torrent (проверьте cookie NNM-Club)",
+                    topic_id
+                ))
+            }
+            TopicPage::NotFound => {
+                return Err(anyhow::anyhow!("Тема NNM-Club {} не найдена", topic_id))
+            }
+        };
+
+        let download_url = format!("{}/download.php?id={}", NNMCLUB_FORUM_URL, download_id);
+        log::info!("Попытка скачивания файла (NNM-Club, тема {})...", topic_id);
+
+        download_torrent_file(
+            &self.http,
+            &self.headers,
+            &download_url,
+            &format!("nnm{}.torrent", topic_id),
+        )
+        .await
+    }
+}
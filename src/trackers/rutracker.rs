@@ -0,0 +1,239 @@
+// src/trackers/rutracker.rs
+
+use super::{CheckOutcome, TrackerProvider};
+use crate::http::HttpClient;
+use crate::rutracker_api::{
+    self, extract_torrent_id_from_comment, get_api_limit_async, get_api_peer_stats_by_hash_async,
+    get_api_topic_data_by_id_async, ApiError, HashLookup, TopicById,
+};
+use crate::topic_id::{TopicId, TopicIdError};
+use crate::torrent::Torrent;
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderValue, COOKIE};
+use std::collections::HashMap;
+
+/// Rutracker: проверка через api.rutracker.cc, скачивание через dl.php с сессионным cookie
+pub struct RutrackerProvider {
+    /// Клиент для api.rutracker.cc
+    http: HttpClient,
+    /// Клиент для скачивания .torrent через dl.php (может идти через другой прокси)
+    download_http: HttpClient,
+    headers: HeaderMap,
+}
+
+impl RutrackerProvider {
+    pub fn new(http: HttpClient, bb_session_cookie: &str) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        let cookie_string = format!("bb_session={}", bb_session_cookie);
+        headers.insert(COOKIE, HeaderValue::from_str(&cookie_string)?);
+
+        Ok(RutrackerProvider {
+            download_http: http.clone(),
+            http,
+            headers,
+        })
+    }
+
+    /// Отдельный клиент для скачивания .torrent файлов
+    pub fn with_download_http(mut self, http: HttpClient) -> Self {
+        self.download_http = http;
+        self
+    }
+}
+
+#[async_trait]
+impl TrackerProvider for RutrackerProvider {
+    fn name(&self) -> &'static str {
+        "Rutracker"
+    }
+
+    fn recognizes(&self, tracker_url: &str) -> bool {
+        tracker_url.contains("rutracker")
+    }
+
+    fn reports_peer_stats(&self) -> bool {
+        true
+    }
+
+    fn extract_topic_id(&self, comment: &str) -> Result<TopicId, TopicIdError> {
+        extract_torrent_id_from_comment(comment)
+    }
+
+    async fn check_torrents(&self, torrents: &mut [Torrent]) -> HashMap<String, CheckOutcome> {
+        // Запрашиваем лимит один раз для всех последующих запросов API!
+        // Он же задает бюджет значений в секунду для ограничителя частоты.
+        let api_limit = match get_api_limit_async(&self.http).await {
+            Ok(lim) => {
+                self.http.limiter().set_budget(lim).await;
+                (lim as usize).min(50)
+            }
+            Err(e) => {
+                log::warn!("⚠️ Не удалось получить лимит API, используем 20: {}", e);
+                20
+            }
+        };
+
+        log::debug!("--- Обновление статистики (сиды/личи) с Rutracker ---");
+        let lookups = get_api_peer_stats_by_hash_async(&self.http, torrents, api_limit).await;
+        log::debug!("✅ Статистика обновлена.");
+
+        let problematic_ids: Vec<TopicId> = torrents
+            .iter()
+            .filter(|t| lookups.get(&t.torrent_hash) == Some(&HashLookup::NotFound))
+            .filter_map(|t| t.torrent_id)
+            .collect();
+
+        let topics_by_id = if problematic_ids.is_empty() {
+            HashMap::new()
+        } else {
+            log::warn!(
+                "--- ⚠️ Обнаружены проблемные торренты (не найдены на Rutracker): {} шт. ---",
+                problematic_ids.len()
+            );
+            log::debug!("Запрос данных тем для проблемных ID...");
+            get_api_topic_data_by_id_async(&self.http, &problematic_ids, api_limit).await
+        };
+
+        torrents
+            .iter()
+            .map(|t| {
+                (
+                    t.torrent_hash.clone(),
+                    outcome_for(t, &lookups, &topics_by_id),
+                )
+            })
+            .collect()
+    }
+
+    async fn download_metainfo(&self, topic_id: TopicId) -> Result<String> {
+        rutracker_api::download_torrent(&self.download_http, &self.headers, topic_id).await
+    }
+}
+
+/// Сводит результаты поиска по хешу и по ID темы в итог проверки торрента
+fn outcome_for(
+    torrent: &Torrent,
+    lookups: &HashMap<String, HashLookup>,
+    topics_by_id: &HashMap<TopicId, TopicById>,
+) -> CheckOutcome {
+    match lookups.get(&torrent.torrent_hash) {
+        Some(HashLookup::Found) => CheckOutcome::Current,
+        Some(HashLookup::Failed(e)) => CheckOutcome::Unknown {
+            error: e.to_string(),
+        },
+        None => CheckOutcome::Unknown {
+            error: ApiError::Missing.to_string(),
+        },
+        Some(HashLookup::NotFound) => {
+            let Some(topic_id) = torrent.torrent_id else {
+                return CheckOutcome::Unknown {
+                    error: "хеш не найден на трекере, а ID темы неизвестен".to_string(),
+                };
+            };
+            match topics_by_id.get(&topic_id) {
+                Some(Ok(Some(data)))
+                    if data.info_hash.eq_ignore_ascii_case(&torrent.torrent_hash) =>
+                {
+                    CheckOutcome::Current
+                }
+                Some(Ok(Some(data))) => CheckOutcome::Changed {
+                    new_hash: data.info_hash.to_lowercase(),
+                    topic: Some(data.info.clone()),
+                },
+                // Только явный null от API означает, что раздача удалена
+                Some(Ok(None)) => CheckOutcome::Removed,
+                Some(Err(e)) => CheckOutcome::Unknown {
+                    error: e.to_string(),
+                },
+                None => CheckOutcome::Unknown {
+                    error: ApiError::Missing.to_string(),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rutracker_api::TopicData;
+    use crate::topic_info::{TopicInfo, TorStatus};
+
+    fn torrent(hash: &str, id: Option<u64>) -> Torrent {
+        Torrent {
+            name: "test".into(),
+            torrent_hash: hash.into(),
+            torrent_id: id.map(TopicId::new),
+            tracker: "".into(),
+            comment: "".into(),
+            state: "".into(),
+            category: "".into(),
+            tags: "".into(),
+            size: 0,
+            seeders: 0,
+            leechers: 0,
+            save_path: "".into(),
+            topic_info: None,
+        }
+    }
+
+    fn topic_data(hash: &str) -> TopicData {
+        TopicData {
+            info_hash: hash.into(),
+            seeders: 1,
+            info: TopicInfo {
+                topic_title: "test".into(),
+                forum_id: 1,
+                poster_id: 1,
+                size: 0,
+                reg_time: 0,
+                tor_status: TorStatus::Verified,
+                seeder_last_seen: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_outcome_only_confirmed_null_is_removed() {
+        let lookups = HashMap::from([
+            ("aa".to_string(), HashLookup::NotFound),
+            ("bb".to_string(), HashLookup::NotFound),
+            ("cc".to_string(), HashLookup::NotFound),
+            (
+                "dd".to_string(),
+                HashLookup::Failed(ApiError::Http("timeout".into())),
+            ),
+        ]);
+        let by_id = HashMap::from([
+            (TopicId::new(1), Ok(None)),
+            (TopicId::new(2), Ok(Some(topic_data("EE")))),
+            (TopicId::new(3), Err(ApiError::Status(502))),
+        ]);
+
+        assert_eq!(
+            outcome_for(&torrent("aa", Some(1)), &lookups, &by_id),
+            CheckOutcome::Removed
+        );
+        assert_eq!(
+            outcome_for(&torrent("bb", Some(2)), &lookups, &by_id),
+            CheckOutcome::Changed {
+                new_hash: "ee".into(),
+                topic: Some(topic_data("EE").info)
+            }
+        );
+        assert!(matches!(
+            outcome_for(&torrent("cc", Some(3)), &lookups, &by_id),
+            CheckOutcome::Unknown { .. }
+        ));
+        assert!(matches!(
+            outcome_for(&torrent("dd", Some(4)), &lookups, &by_id),
+            CheckOutcome::Unknown { .. }
+        ));
+        // Хеш не найден, но ID темы нет — удалять нельзя
+        assert!(matches!(
+            outcome_for(&torrent("aa", None), &lookups, &by_id),
+            CheckOutcome::Unknown { .. }
+        ));
+    }
+}
@@ -0,0 +1,350 @@
+// src/updater.rs
+
+//!
+//! API для встраивания в другие программы: `Updater` собирается через builder с готовыми
+//! клиентами и настройками, а ход работы передается событиями через `tokio::sync::mpsc`
+//!
+
+use crate::control::{Control, TorrentStatus};
+use crate::http::HttpClient;
+use crate::metrics::Metrics;
+use crate::plan::Plan;
+use crate::recheck::Recheck;
+use crate::report::RunReport;
+use crate::shutdown::Shutdown;
+use crate::topic_id::TopicId;
+use crate::torrent::Torrent;
+use crate::trackers::TrackerProvider;
+use crate::{Config, Mode, RunResult, QBIT_ERROR_HINT};
+use anyhow::{Context, Result};
+use qbit_rs::Qbit;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// Торрент, к которому относится событие
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TorrentRef {
+    pub tracker: String,
+    pub name: String,
+    pub hash: String,
+    pub topic_id: Option<TopicId>,
+}
+
+impl TorrentRef {
+    pub fn new(tracker: &str, torrent: &Torrent) -> Self {
+        TorrentRef {
+            tracker: tracker.to_string(),
+            name: torrent.name.clone(),
+            hash: torrent.torrent_hash.clone(),
+            topic_id: torrent.torrent_id,
+        }
+    }
+}
+
+/// Событие хода работы
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum UpdaterEvent {
+    /// Торрент с поддерживаемого трекера найден в qBittorrent
+    TorrentDiscovered(TorrentRef),
+    /// Получена статистика раздачи с трекера (сиды, личи)
+    StatsUpdated(TorrentStatus),
+    /// Начата замена торрента новой версией раздачи
+    UpdateStarted {
+        torrent: TorrentRef,
+        new_hash: String,
+    },
+    /// Новая версия добавлена, старый торрент удален
+    UpdateFinished {
+        torrent: TorrentRef,
+        new_hash: String,
+    },
+    /// Новая версия добавлена, старый торрент остается до ее докачки (`[two_phase]`);
+    /// `UpdateFinished` придет в одном из следующих запусков
+    UpdateStaged {
+        torrent: TorrentRef,
+        new_hash: String,
+    },
+    /// Проверка данных нового торрента завершена (если включена в `[recheck]`)
+    RecheckFinished {
+        torrent: TorrentRef,
+        recheck: Recheck,
+    },
+    /// Торрент удален из клиента вместе с файлами
+    Removed(TorrentRef),
+    /// Ошибка действия с торрентом или всего запуска (`torrent` пуст)
+    Error {
+        torrent: Option<TorrentRef>,
+        action: Option<String>,
+        message: String,
+    },
+    /// Запуск завершен
+    RunFinished(RunReport),
+}
+
+/// Отправитель событий; без подписчика события никуда не отправляются
+#[derive(Clone, Default)]
+pub(crate) struct Events(Option<mpsc::Sender<UpdaterEvent>>);
+
+impl Events {
+    /// Отправляет событие, дожидаясь места в очереди. Закрытый получатель не считается ошибкой.
+    pub(crate) async fn emit(&self, event: UpdaterEvent) {
+        if let Some(sender) = &self.0 {
+            let _ = sender.send(event).await;
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.0.as_ref().is_some_and(|s| !s.is_closed())
+    }
+}
+
+/// Сборщик `Updater`. Не заданные клиенты создаются из конфигурации.
+pub struct UpdaterBuilder {
+    config: Config,
+    client: Option<Qbit>,
+    http: Option<HttpClient>,
+    providers: Option<Vec<Box<dyn TrackerProvider>>>,
+    events: Events,
+    shutdown: Option<Arc<Shutdown>>,
+    control: Option<Arc<Control>>,
+}
+
+impl UpdaterBuilder {
+    /// Готовый клиент qBittorrent (иначе подключение по `config.qbit`)
+    pub fn client(mut self, client: Qbit) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Общий HTTP-клиент для трекеров (иначе создается по `config.http`)
+    pub fn http(mut self, http: HttpClient) -> Self {
+        self.http = Some(http);
+        self
+    }
+
+    /// Свои трекеры вместо заданных в конфигурации
+    pub fn providers(mut self, providers: Vec<Box<dyn TrackerProvider>>) -> Self {
+        self.providers = Some(providers);
+        self
+    }
+
+    /// Пробный запуск: ничего не менять в qBittorrent
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.config.dry_run = dry_run;
+        self
+    }
+
+    /// Канал для событий. Если получатель не успевает их читать, работа ждет его.
+    pub fn events(mut self, sender: mpsc::Sender<UpdaterEvent>) -> Self {
+        self.events = Events(Some(sender));
+        self
+    }
+
+    /// Флаг остановки (например, `Shutdown::listen()` для остановки по Ctrl+C)
+    pub fn shutdown(mut self, shutdown: Arc<Shutdown>) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Состояние для API управления
+    pub fn control(mut self, control: Arc<Control>) -> Self {
+        self.control = Some(control);
+        self
+    }
+
+    /// Создает недостающие клиенты и подключается к qBittorrent
+    pub async fn build(self) -> Result<Updater> {
+        let config = self.config;
+        config.validate()?;
+        let http = match self.http {
+            Some(http) => http,
+            None => HttpClient::new(&config.http)?,
+        };
+        let providers = match self.providers {
+            Some(providers) => providers,
+            None => crate::build_providers(&config, &http)?,
+        };
+        let client = match self.client {
+            Some(client) => client,
+            None => crate::connect(&config).await?,
+        };
+
+        Ok(Updater {
+            metrics: Arc::new(Metrics::new(http.stats())),
+            config,
+            client,
+            providers,
+            events: self.events,
+            shutdown: self.shutdown.unwrap_or_default(),
+            control: self.control,
+        })
+    }
+}
+
+/// Проверка и обновление торрентов одного экземпляра qBittorrent
+pub struct Updater {
+    config: Config,
+    client: Qbit,
+    providers: Vec<Box<dyn TrackerProvider>>,
+    metrics: Arc<Metrics>,
+    events: Events,
+    shutdown: Arc<Shutdown>,
+    control: Option<Arc<Control>>,
+}
+
+impl Updater {
+    pub fn builder(config: Config) -> UpdaterBuilder {
+        UpdaterBuilder {
+            config,
+            client: None,
+            http: None,
+            providers: None,
+            events: Events::default(),
+            shutdown: None,
+            control: None,
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn metrics(&self) -> &Arc<Metrics> {
+        &self.metrics
+    }
+
+    /// Просит завершить текущий запуск: новые действия не начинаются, текущее доводится до конца
+    pub fn stop(&self) {
+        self.shutdown.request();
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.shutdown.is_requested()
+    }
+
+    /// Ждет запроса остановки
+    pub async fn stopped(&self) {
+        self.shutdown.requested().await
+    }
+
+    /// Проверяет торренты и выполняет действия по правилам
+    pub async fn run(&self) -> Result<RunReport> {
+        if let Some(control) = &self.control {
+            control.run_started();
+        }
+        let started = Instant::now();
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let result = crate::process_torrents(
+            &self.client,
+            &self.providers,
+            &self.config,
+            Mode::Run,
+            &self.shutdown,
+            &self.events,
+        )
+        .await;
+
+        let result = match result {
+            Ok(RunResult { report, torrents }) => {
+                self.metrics.record_run(&report, started.elapsed());
+                if let Some(control) = &self.control {
+                    control.run_finished(report.clone(), torrents);
+                }
+                self.events
+                    .emit(UpdaterEvent::RunFinished(report.clone()))
+                    .await;
+                Ok(report)
+            }
+            Err(e) => {
+                self.metrics.record_failure(started_at, started.elapsed());
+                if let Some(control) = &self.control {
+                    control.run_failed(format!("{:#}", e));
+                }
+                self.emit_run_error(&e).await;
+                Err(e).context(QBIT_ERROR_HINT)
+            }
+        };
+
+        self.write_metrics();
+        result
+    }
+
+    /// Проверяет торренты и сохраняет план действий в `output`, ничего не меняя в qBittorrent
+    pub async fn plan(&self, output: &Path) -> Result<RunReport> {
+        let result = crate::process_torrents(
+            &self.client,
+            &self.providers,
+            &self.config,
+            Mode::Plan(output),
+            &self.shutdown,
+            &self.events,
+        )
+        .await;
+
+        match result {
+            Ok(RunResult { report, .. }) => {
+                self.events
+                    .emit(UpdaterEvent::RunFinished(report.clone()))
+                    .await;
+                Ok(report)
+            }
+            Err(e) => {
+                self.emit_run_error(&e).await;
+                Err(e).context(QBIT_ERROR_HINT)
+            }
+        }
+    }
+
+    /// Выполняет сохраненный план, отклоняя действия, для которых что-то изменилось
+    pub async fn apply(&self, plan: Plan) -> Result<RunReport> {
+        let result = crate::apply_plan(
+            &self.client,
+            &self.providers,
+            &self.config,
+            plan,
+            &self.shutdown,
+            &self.events,
+        )
+        .await;
+
+        match result {
+            Ok(report) => {
+                self.events
+                    .emit(UpdaterEvent::RunFinished(report.clone()))
+                    .await;
+                Ok(report)
+            }
+            Err(e) => {
+                self.emit_run_error(&e).await;
+                Err(e).context(QBIT_ERROR_HINT)
+            }
+        }
+    }
+
+    /// Перезаписывает файл метрик для node_exporter, если он задан
+    pub fn write_metrics(&self) {
+        if let Some(path) = &self.config.metrics.textfile_path {
+            if let Err(e) = self.metrics.write_textfile(Path::new(path)) {
+                log::warn!("⚠️ {:#}", e);
+            }
+        }
+    }
+
+    async fn emit_run_error(&self, error: &anyhow::Error) {
+        self.events
+            .emit(UpdaterEvent::Error {
+                torrent: None,
+                action: None,
+                message: format!("{:#}", error),
+            })
+            .await;
+    }
+}
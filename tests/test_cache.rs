@@ -0,0 +1,80 @@
+// tests/test_cache.rs
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tracker_updater::cache::{CachedProperties, PropertiesCache};
+use tracker_updater::topic_info::{TopicInfo, TorStatus};
+
+fn topic() -> TopicInfo {
+    TopicInfo {
+        topic_title: "Ubuntu".to_string(),
+        forum_id: 1379,
+        poster_id: 42,
+        size: 1024,
+        reg_time: 1_709_251_200,
+        tor_status: TorStatus::Verified,
+        seeder_last_seen: 0,
+    }
+}
+
+#[test]
+fn test_cache_roundtrip_and_prune() {
+    let path = Path::new("cache.temp_roundtrip/properties.json");
+    let _ = fs::remove_dir_all("cache.temp_roundtrip");
+
+    let mut cache = PropertiesCache::load(path);
+    assert!(
+        cache.is_empty(),
+        "Отсутствующий файл должен давать пустой кеш"
+    );
+
+    cache.insert(
+        "aaa".to_string(),
+        CachedProperties {
+            comment: "https://rutracker.org/forum/viewtopic.php?t=1".to_string(),
+            topic: None,
+        },
+    );
+    cache.insert(
+        "bbb".to_string(),
+        CachedProperties {
+            comment: "".to_string(),
+            topic: None,
+        },
+    );
+    cache.set_topic("aaa", topic());
+    // Для неизвестного хеша сведения о теме не сохраняются
+    cache.set_topic("ccc", topic());
+    cache.save(path).expect("Не удалось сохранить кеш");
+
+    let mut loaded = PropertiesCache::load(path);
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(
+        loaded.get("aaa").map(|p| p.comment.as_str()),
+        Some("https://rutracker.org/forum/viewtopic.php?t=1")
+    );
+    assert_eq!(
+        loaded.get("aaa").and_then(|p| p.topic.clone()),
+        Some(topic())
+    );
+    assert!(loaded.get("ccc").is_none());
+
+    // Торрента "bbb" больше нет в клиенте
+    loaded.retain_hashes(&HashSet::from(["aaa".to_string()]));
+    assert_eq!(loaded.len(), 1);
+    assert!(loaded.get("bbb").is_none());
+
+    let _ = fs::remove_dir_all("cache.temp_roundtrip");
+}
+
+#[test]
+fn test_cache_corrupted_file_is_ignored() {
+    let path = Path::new("cache.temp_corrupted.json");
+    fs::write(path, "{ это не json").unwrap();
+
+    let cache = PropertiesCache::load(path);
+    assert!(cache.is_empty());
+
+    let _ = fs::remove_file(path);
+}
@@ -4,7 +4,10 @@ use config::{Config as ConfigBuilder, File};
 use std::fs;
 
 // Импортируем нашу основную структуру Config из библиотеки
-use tracker_updater::Config;
+use tracker_updater::http::{HttpClient, ProxyConfig};
+use tracker_updater::rules::{RuleAction, StatusMatch};
+use tracker_updater::topic_info::TorStatus;
+use tracker_updater::{build_providers, Config};
 
 /// Вспомогательная функция для создания временного конфиг-файла
 fn create_temp_config(filename: &str, content: &str) {
@@ -79,8 +82,8 @@ fn test_load_config_missing_field() {
         dry_run = false
 
         [qbit]
-        url = "http://test-url.com"
-        # Поле 'username' отсутствует
+        # Поле 'url' отсутствует (логин и пароль необязательны)
+        username = "test_user"
 
         [rutracker]
         bb_session_cookie = "test_cookie_123"
@@ -97,7 +100,7 @@ fn test_load_config_missing_field() {
     assert!(config_result.is_err());
 
     let error_msg = config_result.err().unwrap().to_string();
-    assert!(error_msg.contains("missing field `username`"));
+    assert!(error_msg.contains("missing field `url`"));
 
     cleanup_temp_config(filename);
 }
@@ -133,3 +136,221 @@ fn test_load_config_invalid_type() {
 
     cleanup_temp_config(filename);
 }
+
+#[test]
+fn test_load_config_rules() {
+    let filename = "config.temp_rules.toml";
+    let content = r#"
+        dry_run = true
+
+        [qbit]
+        url = "http://test-url.com"
+        username = "test_user"
+        password = "test_pass"
+
+        [rutracker]
+        bb_session_cookie = "test_cookie_123"
+
+        [[rules]]
+        name = "закрытые и поглощенные"
+        when = { tor_status = ["closed", "absorbed"] }
+        action = "pause"
+
+        [[rules]]
+        name = "сильно изменился размер"
+        when = { outcome = ["changed"], min_size_change = 0.5 }
+        action = { tag = "check-size" }
+    "#;
+
+    create_temp_config(filename, content);
+
+    let builder = ConfigBuilder::builder().add_source(File::with_name(filename).required(true));
+    let config = builder
+        .build()
+        .expect("Не удалось собрать конфиг")
+        .try_deserialize::<Config>()
+        .expect("Не удалось десериализовать конфиг");
+
+    assert_eq!(config.rules.len(), 2);
+    assert_eq!(config.rules[0].action, RuleAction::Pause);
+    assert_eq!(
+        config.rules[0].when.tor_status,
+        Some(vec![
+            StatusMatch(TorStatus::Closed),
+            StatusMatch(TorStatus::Absorbed)
+        ])
+    );
+    assert_eq!(
+        config.rules[1].action,
+        RuleAction::Tag("check-size".to_string())
+    );
+    assert_eq!(config.rules[1].when.min_size_change, Some(0.5));
+
+    cleanup_temp_config(filename);
+}
+
+#[test]
+fn test_default_rules_when_not_configured() {
+    let filename = "config.temp_no_rules.toml";
+    let content = r#"
+        dry_run = true
+
+        [qbit]
+        url = "http://test-url.com"
+        username = "test_user"
+        password = "test_pass"
+
+        [rutracker]
+        bb_session_cookie = "test_cookie_123"
+    "#;
+
+    create_temp_config(filename, content);
+
+    let builder = ConfigBuilder::builder().add_source(File::with_name(filename).required(true));
+    let config = builder
+        .build()
+        .expect("Не удалось собрать конфиг")
+        .try_deserialize::<Config>()
+        .expect("Не удалось десериализовать конфиг");
+
+    let actions: Vec<RuleAction> = config
+        .effective_rules()
+        .into_iter()
+        .map(|r| r.action)
+        .collect();
+    assert_eq!(actions, vec![RuleAction::Update, RuleAction::Delete]);
+
+    cleanup_temp_config(filename);
+}
+
+#[test]
+fn test_load_config_tracker_proxies() {
+    let filename = "config.temp_proxies.toml";
+    let content = r#"
+        dry_run = true
+
+        [qbit]
+        url = "http://127.0.0.1:8080"
+        username = "admin"
+        password = "admin"
+
+        [http]
+        proxy = "http://default-proxy:3128"
+
+        [rutracker]
+        bb_session_cookie = "cookie"
+        api_proxy = "socks5h://127.0.0.1:1080"
+        download_proxy = { url = "socks5://proxy.local:1080", username = "user", password = "secret" }
+    "#;
+
+    create_temp_config(filename, content);
+
+    let config = ConfigBuilder::builder()
+        .add_source(File::with_name(filename).required(true))
+        .build()
+        .expect("Не удалось собрать конфиг")
+        .try_deserialize::<Config>()
+        .expect("Не удалось десериализовать конфиг");
+
+    assert_eq!(
+        config.http.proxy,
+        Some(ProxyConfig::Url("http://default-proxy:3128".into()))
+    );
+    assert_eq!(
+        config.rutracker.api_proxy,
+        Some(ProxyConfig::Url("socks5h://127.0.0.1:1080".into()))
+    );
+    assert_eq!(
+        config.rutracker.download_proxy,
+        Some(ProxyConfig::WithAuth {
+            url: "socks5://proxy.local:1080".into(),
+            username: "user".into(),
+            password: "secret".into(),
+        })
+    );
+    assert!(config.rutracker.proxy.is_none());
+
+    // Клиенты с прокси создаются без обращения к сети
+    let http = HttpClient::new(&config.http).unwrap();
+    assert_eq!(build_providers(&config, &http).unwrap().len(), 1);
+
+    cleanup_temp_config(filename);
+}
+
+#[test]
+fn test_load_config_qbit_connection_options() {
+    let filename = "config.temp_qbit_connection.toml";
+    let content = r#"
+        dry_run = true
+
+        [qbit]
+        url = "https://qbit.example.com"
+        accept_invalid_certs = true
+        basic_auth = { username = "nginx", password = "secret" }
+        headers = { "X-Api-Key" = "abc" }
+
+        [rutracker]
+        bb_session_cookie = "cookie"
+    "#;
+
+    create_temp_config(filename, content);
+
+    let config = ConfigBuilder::builder()
+        .add_source(File::with_name(filename).required(true))
+        .build()
+        .expect("Не удалось собрать конфиг")
+        .try_deserialize::<Config>()
+        .expect("Не удалось десериализовать конфиг");
+
+    // Логин не задан: вход без пароля (обход авторизации для localhost)
+    assert!(config.qbit.username.is_empty());
+    assert!(config.qbit.connection.accept_invalid_certs);
+    assert!(config.qbit.connection.ca_cert.is_none());
+    assert_eq!(
+        config.qbit.connection.basic_auth.as_ref().unwrap().username,
+        "nginx"
+    );
+    // config приводит ключи к нижнему регистру; для имен заголовков регистр не важен
+    assert_eq!(config.qbit.connection.headers["x-api-key"], "abc");
+
+    cleanup_temp_config(filename);
+}
+
+#[test]
+fn test_validate_rejects_move_while_old_version_seeds() {
+    let filename = "config.temp_keep_previous.toml";
+    let base = r#"
+        dry_run = false
+
+        [qbit]
+        url = "http://localhost:8080"
+
+        [rutracker]
+        bb_session_cookie = "test"
+
+        [two_phase]
+        enabled = true
+        keep_old = "seed"
+        add_paused = true
+
+        [keep_previous]
+        enabled = true
+    "#;
+    let load = |extra: &str| -> Config {
+        create_temp_config(filename, &format!("{}{}", base, extra));
+        let config = ConfigBuilder::builder()
+            .add_source(File::with_name(filename).required(true))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        cleanup_temp_config(filename);
+        config
+    };
+
+    // Перенос забрал бы файлы у раздающейся старой версии
+    let error = load("mode = \"move\"").validate().unwrap_err().to_string();
+    assert!(error.contains("mode = \"copy\""));
+
+    assert!(load("mode = \"copy\"").validate().is_ok());
+}
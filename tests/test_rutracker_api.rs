@@ -1,6 +1,8 @@
 // tests/test_rutracker_api.rs
 // cargo test --test test_rutracker_api -- --test-threads=1
-use tracker_updater::rutracker_api::get_api_peer_stats_by_hash_async;
+use tracker_updater::http::{HttpClient, HttpConfig};
+use tracker_updater::rutracker_api::{get_api_peer_stats_by_hash_async, HashLookup};
+use tracker_updater::topic_id::{TopicId, TopicIdError};
 use tracker_updater::torrent::Torrent;
 
 // Импортируем публичные функции из нашего крейта
@@ -15,32 +17,103 @@ use tracker_updater::rutracker_api::{
 #[test]
 fn test_extract_id_happy_path() {
     let comment = "Какой-то текст https://rutracker.org/forum/viewtopic.php?t=1234567";
-    assert_eq!(extract_torrent_id_from_comment(comment), "1234567");
+    assert_eq!(
+        extract_torrent_id_from_comment(comment),
+        Ok(TopicId::new(1234567))
+    );
 }
 
 #[test]
 fn test_extract_id_no_id() {
     let comment = "Здесь нет ID";
-    assert_eq!(extract_torrent_id_from_comment(comment), "");
+    assert_eq!(
+        extract_torrent_id_from_comment(comment),
+        Err(TopicIdError::NotFound)
+    );
 }
 
 #[test]
 fn test_extract_id_empty_string() {
     let comment = "";
-    assert_eq!(extract_torrent_id_from_comment(comment), "");
+    assert_eq!(
+        extract_torrent_id_from_comment(comment),
+        Err(TopicIdError::NotFound)
+    );
 }
 
 #[test]
 fn test_extract_id_just_t_equals() {
-    let comment = "t=";
-    assert_eq!(extract_torrent_id_from_comment(comment), "");
+    let comment = "https://rutracker.org/forum/viewtopic.php?t=";
+    assert_eq!(
+        extract_torrent_id_from_comment(comment),
+        Err(TopicIdError::Empty)
+    );
+}
+
+#[test]
+fn test_extract_id_with_extra_params_and_fragment() {
+    // Раньше сюда попадал весь хвост после "t="
+    assert_eq!(
+        extract_torrent_id_from_comment("https://rutracker.org/forum/viewtopic.php?t=123&start=30"),
+        Ok(TopicId::new(123))
+    );
+    assert_eq!(
+        extract_torrent_id_from_comment("https://rutracker.org/forum/dl.php?t=5#x"),
+        Ok(TopicId::new(5))
+    );
+}
+
+#[test]
+fn test_extract_id_mirrors() {
+    assert_eq!(
+        extract_torrent_id_from_comment("http://rutracker.net/forum/viewtopic.php?t=42"),
+        Ok(TopicId::new(42))
+    );
+    assert_eq!(
+        extract_torrent_id_from_comment("rutracker.nl/forum/viewtopic.php?t=43"),
+        Ok(TopicId::new(43))
+    );
+}
+
+#[test]
+fn test_extract_id_not_numeric() {
+    assert_eq!(
+        extract_torrent_id_from_comment("https://rutracker.org/forum/viewtopic.php?t=12abc"),
+        Err(TopicIdError::NotNumeric("12abc".to_string()))
+    );
+}
+
+#[test]
+fn test_extract_id_foreign_tracker() {
+    let comment = "https://example.com/forum/viewtopic.php?t=111";
+    assert_eq!(
+        extract_torrent_id_from_comment(comment),
+        Err(TopicIdError::NotFound)
+    );
 }
 
 #[test]
 fn test_extract_id_multiple_t() {
     // Должен взять последнее вхождение
-    let comment = "t=111 а потом t=222";
-    assert_eq!(extract_torrent_id_from_comment(comment), "222");
+    let comment = "https://rutracker.org/forum/viewtopic.php?t=111 а потом https://rutracker.org/forum/viewtopic.php?t=222";
+    assert_eq!(
+        extract_torrent_id_from_comment(comment),
+        Ok(TopicId::new(222))
+    );
+}
+
+#[test]
+fn test_extract_id_multiple_links() {
+    // Ссылка с некорректным ID пропускается
+    let comment = "https://rutracker.org/forum/viewtopic.php?t=abc а потом https://rutracker.org/forum/viewtopic.php?t=222";
+    assert_eq!(
+        extract_torrent_id_from_comment(comment),
+        Ok(TopicId::new(222))
+    );
+}
+
+fn http_client() -> HttpClient {
+    HttpClient::new(&HttpConfig::default()).expect("Не удалось создать HTTP-клиент")
 }
 
 // --- Интеграционный тест для get_api_limit_async ---
@@ -48,7 +121,7 @@ fn test_extract_id_multiple_t() {
 #[tokio::test]
 async fn test_get_api_limit() {
     // Вызываем асинхронную функцию
-    let result = get_api_limit_async().await;
+    let result = get_api_limit_async(&http_client()).await;
 
     // 1. Проверяем, что запрос завершился успешно (Result::Ok)
     assert!(result.is_ok(), "Запрос к API не удался: {:?}", result.err());
@@ -63,21 +136,20 @@ async fn test_get_api_limit() {
 #[tokio::test]
 async fn test_get_api_torrent_hash_by_id_basic() {
     // 1. Определяем ID для запроса, используя ваши данные
-    // Мы используем &str, так как функция дженерик
-    let ids_to_test = vec!["1", "2142"];
+    let ids_to_test = vec![TopicId::new(1), TopicId::new(2142)];
 
     // 2. Вызываем асинхронную функцию
+    let hashes_map = get_api_torrent_hash_by_id_async(&http_client(), &ids_to_test, 10).await;
 
-    let result = get_api_torrent_hash_by_id_async(&ids_to_test, 10).await;
-    // 3. Проверяем, что запрос завершился успешно (Result::Ok)
-    assert!(
-        result.is_ok(),
-        "Запрос get_tor_hash не удался: {:?}",
-        result.err()
-    );
-
-    // 4. Распаковываем результат (HashMap)
-    let hashes_map = result.unwrap();
+    // 3. Проверяем, что запрос по каждому ID завершился успешно (Result::Ok)
+    for (id, result) in &hashes_map {
+        assert!(
+            result.is_ok(),
+            "Запрос get_tor_hash для ID {} не удался: {:?}",
+            id,
+            result
+        );
+    }
 
     // 5. Проверяем, что карта содержит 2 элемента
     assert_eq!(
@@ -90,8 +162,8 @@ async fn test_get_api_torrent_hash_by_id_basic() {
     // 6. Проверяем ID "1" (ожидаем null/None)
     // API возвращает null, что serde парсит в Option::None
     assert_eq!(
-        hashes_map.get("1"),
-        Some(&None),
+        hashes_map.get(&TopicId::new(1)),
+        Some(&Ok(None)),
         "ID 1 должен иметь значение None (null)"
     );
 
@@ -99,8 +171,8 @@ async fn test_get_api_torrent_hash_by_id_basic() {
     // API возвращает хэш, что serde парсит в Option::Some(String)
     let expected_hash = "658EDAB6AF0B424E62FEFEC0E39DBE2AC55B9AE3".to_string();
     assert_eq!(
-        hashes_map.get("2142"),
-        Some(&Some(expected_hash)),
+        hashes_map.get(&TopicId::new(2142)),
+        Some(&Ok(Some(expected_hash))),
         "ID 2142 имеет неверный хэш"
     );
 }
@@ -108,28 +180,24 @@ async fn test_get_api_torrent_hash_by_id_basic() {
 #[tokio::test]
 async fn test_get_api_torrent_hash_by_id_empty_and_types() {
     // 1. Тест с пустым вектором
-    let empty_ids: Vec<u32> = vec![];
-    let result_empty = get_api_torrent_hash_by_id_async(&empty_ids, 10).await;
+    let empty_ids: Vec<TopicId> = vec![];
+    let result_empty = get_api_torrent_hash_by_id_async(&http_client(), &empty_ids, 10).await;
 
-    assert!(result_empty.is_ok(), "Запрос с пустым вектором не удался");
     assert!(
-        result_empty.unwrap().is_empty(),
+        result_empty.is_empty(),
         "Результат для пустого вектора должен быть пустым HashMap"
     );
 
-    // 2. Тест с u32 (проверка дженерика <T>)
-    let u32_ids = vec![2142];
-    let result_u32 = get_api_torrent_hash_by_id_async(&u32_ids, 10).await;
-    assert!(result_u32.is_ok());
-
-    let map_u32 = result_u32.unwrap();
+    // 2. Тест с ID, созданным из u64
+    let u64_ids = vec![TopicId::from(2142)];
+    let map_u64 = get_api_torrent_hash_by_id_async(&http_client(), &u64_ids, 10).await;
     let expected_hash = "658EDAB6AF0B424E62FEFEC0E39DBE2AC55B9AE3".to_string();
 
-    // Ключ в HashMap всегда String, даже если на входе u32
+    // Строковые ключи ответа API разбираются в TopicId
     assert_eq!(
-        map_u32.get("2142"),
-        Some(&Some(expected_hash)),
-        "Ключ '2142' (из u32) не найден или хэш неверен"
+        map_u64.get(&TopicId::new(2142)),
+        Some(&Ok(Some(expected_hash))),
+        "Ключ '2142' не найден или хэш неверен"
     );
 }
 
@@ -140,7 +208,7 @@ async fn test_get_api_peer_stats_by_hash() {
             name: "Test valid".to_string(), // Имя может отличаться в вашем коде
             // хеш переведен в нижний регистр
             torrent_hash: "658edab6af0b424e62fefec0e39dbe2ac55b9ae3".to_string(),
-            torrent_id: "2142".to_string(),
+            torrent_id: Some(TopicId::new(2142)),
             tracker: "".to_string(),
             comment: "".to_string(),
             state: "".to_string(),
@@ -150,12 +218,13 @@ async fn test_get_api_peer_stats_by_hash() {
             seeders: 0,
             leechers: 0,
             save_path: "".to_string(),
+            topic_info: None,
         },
         Torrent {
             name: "Test invalid".to_string(),
             // хеш переведен в нижний регистр
             torrent_hash: "invalidhash123".to_string(),
-            torrent_id: "9999999".to_string(),
+            torrent_id: Some(TopicId::new(9999999)),
             tracker: "".to_string(),
             comment: "".to_string(),
             state: "".to_string(),
@@ -165,28 +234,40 @@ async fn test_get_api_peer_stats_by_hash() {
             seeders: 0,
             leechers: 0,
             save_path: "".to_string(),
+            topic_info: None,
         },
     ];
 
-    let result = get_api_peer_stats_by_hash_async(&mut torrents, 1).await;
+    let lookups = get_api_peer_stats_by_hash_async(&http_client(), &mut torrents, 1).await;
 
-    assert!(result.is_ok());
-
-    let problematic_ids = result.unwrap();
+    assert_eq!(
+        lookups.get("658edab6af0b424e62fefec0e39dbe2ac55b9ae3"),
+        Some(&HashLookup::Found),
+        "Валидный хеш должен быть найден"
+    );
 
     // Хеш 658EDAB6AF0B424E62FEFEC0E39DBE2AC55B9AE3 должен быть обновлен
     assert!(
         torrents[0].seeders > 0,
         "Сиды должны обновиться для валидного хеша"
     );
-    assert_eq!(
-        torrents[0].leechers, 0,
-        "Личи всегда должны быть 0 в новом API"
+    assert!(
+        torrents[0].topic_info.is_some(),
+        "Сведения о теме должны заполниться для валидного хеша"
     );
-
-    // Неверный хеш возвращается как problematic_id
+    // Статистика пиров берется из get_peer_stats: известно, когда в последний раз видели сида
     assert!(
-        problematic_ids.contains(&"9999999".to_string()),
-        "Недействительный ID должен попасть в проблемные"
+        torrents[0]
+            .topic_info
+            .as_ref()
+            .is_some_and(|t| t.seeder_last_seen > 0),
+        "Время последнего сида должно заполниться из get_peer_stats"
+    );
+
+    // Неверный хеш API явно отвергает — он считается ненайденным
+    assert_eq!(
+        lookups.get("invalidhash123"),
+        Some(&HashLookup::NotFound),
+        "Недействительный хеш должен считаться ненайденным"
     );
 }
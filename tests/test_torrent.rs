@@ -1,6 +1,8 @@
 // tests/test_torrent.rs
 
 // Импортируем структуру Torrent из нашего крейта (библиотеки)
+use tracker_updater::topic_id::TopicId;
+use tracker_updater::topic_info::{TopicInfo, TorStatus};
 use tracker_updater::torrent::Torrent;
 
 #[test]
@@ -8,7 +10,7 @@ fn test_torrent_display_full() {
     let torrent = Torrent {
         name: "My Test Torrent".to_string(),
         torrent_hash: "aabbcc112233".to_string(),
-        torrent_id: "98765".to_string(),
+        torrent_id: Some(TopicId::new(98765)),
         tracker: "http://example-tracker.com".to_string(),
         comment: "Test comment t=98765".to_string(),
         state: "Downloading".to_string(),
@@ -18,6 +20,7 @@ fn test_torrent_display_full() {
         seeders: 10,
         leechers: 2,
         save_path: "/downloads/movies".to_string(),
+        topic_info: None,
     };
 
     // Генерируем строку с помощью `format!`
@@ -43,7 +46,7 @@ fn test_torrent_display_minimal() {
     let torrent = Torrent {
         name: "Minimal Torrent".to_string(),
         torrent_hash: "min456".to_string(),
-        torrent_id: "123".to_string(),
+        torrent_id: Some(TopicId::new(123)),
         tracker: "".to_string(), // Пусто
         comment: "".to_string(), // Пусто
         state: "Paused".to_string(),
@@ -53,6 +56,7 @@ fn test_torrent_display_minimal() {
         seeders: 0,
         leechers: 0,
         save_path: "".to_string(), // Пусто
+        topic_info: None,
     };
 
     let display_str = format!("{}", torrent);
@@ -67,6 +71,43 @@ fn test_torrent_display_minimal() {
     assert!(!display_str.contains("Комментарий:"));
     assert!(!display_str.contains("Путь:"));
     assert!(!display_str.contains("Теги:"));
+    assert!(!display_str.contains("Тема:"));
+}
+
+#[test]
+fn test_torrent_display_topic_info() {
+    let torrent = Torrent {
+        name: "Topic Torrent".to_string(),
+        torrent_hash: "abc".to_string(),
+        torrent_id: Some(TopicId::new(2142)),
+        tracker: "".to_string(),
+        comment: "".to_string(),
+        state: "Uploading".to_string(),
+        tags: "".to_string(),
+        category: "".to_string(),
+        size: 0,
+        seeders: 0,
+        leechers: 0,
+        save_path: "".to_string(),
+        topic_info: Some(TopicInfo {
+            topic_title: "Ubuntu 24.04 LTS".to_string(),
+            forum_id: 1379,
+            poster_id: 42,
+            size: 6 * 1024 * 1024 * 1024,
+            reg_time: 1_709_251_200,
+            tor_status: TorStatus::Verified,
+            seeder_last_seen: 0,
+        }),
+    };
+
+    let display_str = format!("{}", torrent);
+
+    assert!(display_str.contains("Тема: Ubuntu 24.04 LTS"));
+    assert!(display_str.contains("Раздел: 1379"));
+    assert!(display_str.contains("Статус раздачи: проверено"));
+    assert!(display_str.contains("Размер на трекере: 6144 MB"));
+    assert!(display_str.contains("Зарегистрирован: 2024-03-01"));
+    assert!(!display_str.contains("Сид последний раз"));
 }
 
 #[test]
@@ -74,7 +115,7 @@ fn test_torrent_debug_compact() {
     let torrent = Torrent {
         name: "My Test Torrent".to_string(),
         torrent_hash: "aabbcc112233".to_string(),
-        torrent_id: "98765".to_string(),
+        torrent_id: Some(TopicId::new(98765)),
         tracker: "http://example-tracker.com".to_string(),
         comment: "Test comment t=98765".to_string(),
         state: "Downloading".to_string(),
@@ -84,6 +125,7 @@ fn test_torrent_debug_compact() {
         seeders: 10,
         leechers: 2,
         save_path: "/downloads/movies".to_string(),
+        topic_info: None,
     };
 
     // Генерируем строку с помощью `format!` и `{:?}`
@@ -113,3 +155,40 @@ fn test_torrent_debug_compact() {
     assert!(debug_str.contains("tags: \"HD, Action\""));
     assert!(debug_str.contains("category: \"Movies\""));
 }
+
+#[test]
+fn test_days_without_seeders() {
+    const DAY: i64 = 86_400;
+    let mut torrent = Torrent {
+        name: "Dead Torrent".to_string(),
+        torrent_hash: "dead".to_string(),
+        torrent_id: Some(TopicId::new(1)),
+        tracker: "".to_string(),
+        comment: "".to_string(),
+        state: "".to_string(),
+        tags: "".to_string(),
+        category: "".to_string(),
+        size: 0,
+        seeders: 0,
+        leechers: 4,
+        save_path: "".to_string(),
+        topic_info: None,
+    };
+
+    // Без сведений о теме неизвестно, когда был последний сид
+    assert_eq!(torrent.days_without_seeders(100 * DAY), None);
+
+    torrent.topic_info = Some(TopicInfo {
+        topic_title: "Dead".to_string(),
+        forum_id: 1,
+        poster_id: 1,
+        size: 0,
+        reg_time: DAY,
+        tor_status: TorStatus::Verified,
+        seeder_last_seen: 10 * DAY,
+    });
+    assert_eq!(torrent.days_without_seeders(40 * DAY + 5), Some(30));
+
+    torrent.seeders = 1;
+    assert_eq!(torrent.days_without_seeders(40 * DAY), None);
+}
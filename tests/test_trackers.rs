@@ -0,0 +1,99 @@
+// tests/test_trackers.rs
+
+use tracker_updater::http::{HttpClient, HttpConfig};
+use tracker_updater::topic_id::{TopicId, TopicIdError};
+use tracker_updater::trackers::nnmclub::{parse_topic_page, TopicPage};
+use tracker_updater::trackers::{
+    find_provider, NnmClubProvider, RutrackerProvider, TrackerProvider,
+};
+
+const FIXTURE_ACTIVE: &str = include_str!("fixtures/nnmclub/topic_active.html");
+const FIXTURE_GUEST: &str = include_str!("fixtures/nnmclub/topic_guest.html");
+const FIXTURE_NOT_FOUND: &str = include_str!("fixtures/nnmclub/topic_not_found.html");
+const FIXTURE_MAINTENANCE: &str = include_str!("fixtures/nnmclub/maintenance.html");
+
+fn providers() -> Vec<Box<dyn TrackerProvider>> {
+    let http = HttpClient::new(&HttpConfig::default()).unwrap();
+    vec![
+        Box::new(RutrackerProvider::new(http.clone(), "test_cookie").unwrap()),
+        Box::new(NnmClubProvider::new(http, "phpbb2mysql_4_sid=test").unwrap()),
+    ]
+}
+
+// --- Разбор страниц NNM-Club ---
+
+#[test]
+fn test_nnmclub_parse_active_topic() {
+    let page = parse_topic_page(FIXTURE_ACTIVE).unwrap();
+    assert_eq!(
+        page,
+        TopicPage::Active {
+            info_hash: "9f9165d9a281a9b8e782cd5176bbcc8256fd1871".to_string(),
+            download_id: Some(1209876),
+        }
+    );
+}
+
+#[test]
+fn test_nnmclub_parse_guest_topic_without_download_link() {
+    let page = parse_topic_page(FIXTURE_GUEST).unwrap();
+    assert_eq!(
+        page,
+        TopicPage::Active {
+            info_hash: "9f9165d9a281a9b8e782cd5176bbcc8256fd1871".to_string(),
+            download_id: None,
+        }
+    );
+}
+
+#[test]
+fn test_nnmclub_parse_not_found() {
+    assert_eq!(
+        parse_topic_page(FIXTURE_NOT_FOUND).unwrap(),
+        TopicPage::NotFound
+    );
+}
+
+#[test]
+fn test_nnmclub_parse_unexpected_page_is_error() {
+    // Страница-заглушка не должна приниматься за удаленную тему
+    assert!(parse_topic_page(FIXTURE_MAINTENANCE).is_err());
+}
+
+#[test]
+fn test_nnmclub_not_found_requires_forum_layout() {
+    // Текст есть, но это не страница форума: тема не считается удаленной
+    let page = "<html><head><title>Ошибка</title></head><body>Тема не найдена</body></html>";
+    assert!(parse_topic_page(page).is_err());
+}
+
+// --- Выбор трекера и ID темы ---
+
+#[test]
+fn test_find_provider_by_tracker_url() {
+    let providers = providers();
+
+    let rutracker = find_provider(&providers, "http://bt.rutracker.cc/ann?uk=xxx").unwrap();
+    assert_eq!(rutracker.name(), "Rutracker");
+
+    let nnm = find_provider(&providers, "http://bt01.nnm-club.cc:2710/xxx/announce").unwrap();
+    assert_eq!(nnm.name(), "NNM-Club");
+
+    assert!(find_provider(&providers, "udp://tracker.example.org:6969").is_none());
+}
+
+#[test]
+fn test_provider_extract_topic_id() {
+    let providers = providers();
+    let nnm = find_provider(&providers, "http://bt01.nnm-club.cc:2710/announce").unwrap();
+
+    assert_eq!(
+        nnm.extract_topic_id("https://nnmclub.to/forum/viewtopic.php?t=1712345"),
+        Ok(TopicId::new(1712345))
+    );
+    // Ссылка на другой трекер для NNM-Club не подходит
+    assert_eq!(
+        nnm.extract_topic_id("https://rutracker.org/forum/viewtopic.php?t=1712345"),
+        Err(TopicIdError::NotFound)
+    );
+}
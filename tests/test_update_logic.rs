@@ -106,14 +106,14 @@ async fn add_test_torrent(
 
                     let props = client.get_torrent_properties(t_hash).await?;
                     let comment = props.comment.unwrap_or_default();
-                    let torrent_id = rutracker_api::extract_torrent_id_from_comment(&comment);
-
-                    if torrent_id.is_empty() {
-                        panic!(
-                            "В комментарии торрента {} нет ID rutracker!",
-                            torrent_file_path
-                        );
-                    }
+                    let torrent_id = match rutracker_api::extract_torrent_id_from_comment(&comment)
+                    {
+                        Ok(id) => id.to_string(),
+                        Err(e) => panic!(
+                            "В комментарии торрента {} нет ID rutracker: {}",
+                            torrent_file_path, e
+                        ),
+                    };
 
                     let category_non_empty = NonEmptyStr::new(category)
                         .expect("Тестовая категория не должна быть пустой");
@@ -292,7 +292,9 @@ async fn test_full_update_scenario() {
                 let props = client.get_torrent_properties(hash).await.unwrap();
                 let id = rutracker_api::extract_torrent_id_from_comment(
                     &props.comment.unwrap_or_default(),
-                );
+                )
+                .map(|id| id.to_string())
+                .unwrap_or_default();
 
                 final_hashes_map.insert(hash.clone(), (id.clone(), abs_path.clone()));
                 final_ids_map.insert(id, (hash.clone(), abs_path));
@@ -401,7 +403,9 @@ async fn test_update_preserves_category_and_tags() {
                 let props = client.get_torrent_properties(hash).await.unwrap();
                 let id = rutracker_api::extract_torrent_id_from_comment(
                     &props.comment.unwrap_or_default(),
-                );
+                )
+                .map(|id| id.to_string())
+                .unwrap_or_default();
 
                 final_torrents_map.insert(hash.clone(), t.clone());
                 if !id.is_empty() {
@@ -490,7 +494,9 @@ async fn test_dry_run_scenario() {
                 let props = client.get_torrent_properties(hash).await.unwrap();
                 let id = rutracker_api::extract_torrent_id_from_comment(
                     &props.comment.unwrap_or_default(),
-                );
+                )
+                .map(|id| id.to_string())
+                .unwrap_or_default();
                 final_hashes_map.insert(hash.clone(), id);
             }
         }
@@ -0,0 +1,762 @@
+// tests/test_updater.rs
+
+use async_trait::async_trait;
+use axum::{
+    body::Bytes,
+    extract::{RawQuery, State},
+    http::{StatusCode, Uri},
+    routing::{get, post},
+    Json, Router,
+};
+use config::{Config as ConfigBuilder, File, FileFormat};
+use qbit_rs::Qbit;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracker_updater::pending::{PendingUpdate, PendingUpdates};
+use tracker_updater::plan::Plan;
+use tracker_updater::report::ReportAction;
+use tracker_updater::topic_id::{TopicId, TopicIdError};
+use tracker_updater::torrent::Torrent;
+use tracker_updater::trackers::{CheckOutcome, TrackerProvider};
+use tracker_updater::updater::{Updater, UpdaterEvent};
+use tracker_updater::Config;
+
+/// Трекер для тестов: первая раздача актуальна, для второй возвращается `second`
+struct FakeProvider {
+    second: CheckOutcome,
+    dir: PathBuf,
+}
+
+#[async_trait]
+impl TrackerProvider for FakeProvider {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn recognizes(&self, tracker_url: &str) -> bool {
+        tracker_url.contains("fake.tracker")
+    }
+
+    fn reports_peer_stats(&self) -> bool {
+        true
+    }
+
+    fn extract_topic_id(&self, comment: &str) -> Result<TopicId, TopicIdError> {
+        comment.parse()
+    }
+
+    async fn check_torrents(&self, torrents: &mut [Torrent]) -> HashMap<String, CheckOutcome> {
+        torrents
+            .iter_mut()
+            .map(|t| {
+                let outcome = if t.torrent_hash == "aaa" {
+                    t.seeders = 5;
+                    CheckOutcome::Current
+                } else {
+                    self.second.clone()
+                };
+                (t.torrent_hash.clone(), outcome)
+            })
+            .collect()
+    }
+
+    /// Новая версия — `metainfo.torrent` из папки теста, если он есть
+    async fn download_metainfo(&self, topic_id: TopicId) -> anyhow::Result<String> {
+        let path = self.dir.join(format!("{}.torrent", topic_id));
+        let data = std::fs::read(self.dir.join("metainfo.torrent"))
+            .unwrap_or_else(|_| b"d4:infod6:lengthi5000000e4:name3:newee".to_vec());
+        std::fs::write(&path, data)?;
+        Ok(path.to_string_lossy().into_owned())
+    }
+}
+
+/// Вызовы, изменяющие состояние qBittorrent: путь и тело запроса
+type Calls = Arc<Mutex<Vec<String>>>;
+
+/// Состояние мока qBittorrent
+#[derive(Clone)]
+struct MockQbit {
+    calls: Calls,
+    /// Запросы к этому методу завершаются ошибкой (например, `/add`)
+    fail: Option<&'static str>,
+    /// Файлы торрента `bbb` (`torrents/files`)
+    files: Value,
+    /// Его .torrent (`torrents/export`)
+    export: Vec<u8>,
+}
+
+impl MockQbit {
+    fn new(calls: Calls) -> Self {
+        MockQbit {
+            calls,
+            fail: None,
+            files: json!([{"index": 0, "name": "new", "size": 100, "progress": 1.0, "priority": 1}]),
+            export: b"d4:infodee".to_vec(),
+        }
+    }
+}
+
+/// Минимальный qBittorrent WebUI. Новый торрент с хешем `new` после проверки
+/// содержит только 10% данных.
+async fn mock_qbit(calls: Calls) -> String {
+    serve_mock_qbit(MockQbit::new(calls)).await
+}
+
+/// qBittorrent, который отвечает ошибкой на запросы к `endpoint` (например, `/add`)
+async fn mock_qbit_failing(calls: Calls, endpoint: &'static str) -> String {
+    serve_mock_qbit(MockQbit {
+        fail: Some(endpoint),
+        ..MockQbit::new(calls)
+    })
+    .await
+}
+
+/// qBittorrent, в котором у торрента `bbb` файлы `files` и .torrent `export`
+async fn mock_qbit_with_torrent(calls: Calls, files: Value, export: Vec<u8>) -> String {
+    serve_mock_qbit(MockQbit {
+        files,
+        export,
+        ..MockQbit::new(calls)
+    })
+    .await
+}
+
+async fn serve_mock_qbit(mock: MockQbit) -> String {
+    async fn torrents(RawQuery(query): RawQuery) -> Json<Value> {
+        if let Some(query) = query.filter(|q| q.contains("new")) {
+            let mut list = vec![
+                json!({"hash": "new", "name": "Вторая", "state": "stalledDL", "progress": 0.1, "size": 2048}),
+            ];
+            if query.contains("bbb") {
+                list.push(
+                    json!({"hash": "bbb", "name": "Вторая", "state": "pausedUP", "progress": 1.0}),
+                );
+            }
+            return Json(Value::Array(list));
+        }
+        Json(json!([
+            {"hash": "AAA", "name": "Первая", "tracker": "http://fake.tracker/announce",
+             "state": "uploading", "category": "", "tags": "", "size": 1024, "save_path": "/data"},
+            {"hash": "bbb", "name": "Вторая", "tracker": "http://fake.tracker/announce",
+             "state": "uploading", "category": "", "tags": "", "size": 2048, "save_path": "/data"},
+            {"hash": "ccc", "name": "Чужая", "tracker": "http://other.tracker/announce"}
+        ]))
+    }
+    async fn properties(RawQuery(query): RawQuery) -> Json<Value> {
+        let comment = if query.as_deref() == Some("hash=aaa") {
+            "101"
+        } else {
+            "102"
+        };
+        Json(json!({ "comment": comment }))
+    }
+    async fn export(State(mock): State<MockQbit>) -> Vec<u8> {
+        mock.export
+    }
+    async fn maindata() -> Json<Value> {
+        Json(json!({"rid": 1, "server_state": {"free_space_on_disk": 1_000_000}}))
+    }
+    async fn files(State(mock): State<MockQbit>) -> Json<Value> {
+        Json(mock.files)
+    }
+    async fn record(
+        State(MockQbit { calls, fail, .. }): State<MockQbit>,
+        uri: Uri,
+        body: Bytes,
+    ) -> StatusCode {
+        let body = String::from_utf8_lossy(&body);
+        calls
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", uri.path(), body));
+        if fail.is_some_and(|endpoint| uri.path().ends_with(endpoint)) {
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        } else {
+            StatusCode::OK
+        }
+    }
+
+    let app = Router::new()
+        .route("/api/v2/torrents/info", get(torrents))
+        .route("/api/v2/torrents/properties", get(properties))
+        .route("/api/v2/torrents/export", get(export))
+        .route("/api/v2/torrents/files", get(files))
+        .route("/api/v2/sync/maindata", get(maindata))
+        .route("/api/v2/torrents/add", post(record))
+        .route("/api/v2/torrents/delete", post(record))
+        .route("/api/v2/torrents/stop", post(record))
+        .with_state(mock);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+fn test_config(url: &str, dir: &Path, extra: &str) -> Config {
+    let content = format!(
+        r#"
+        dry_run = false
+        cache_path = "{dir}/cache.json"
+        ignore_path = "{dir}/ignored.json"
+
+        [qbit]
+        url = "{url}"
+
+        [rutracker]
+        bb_session_cookie = "test_cookie"
+
+        {extra}
+        "#,
+        dir = dir.display()
+    );
+    ConfigBuilder::builder()
+        .add_source(File::from_str(&content, FileFormat::Toml))
+        .build()
+        .unwrap()
+        .try_deserialize()
+        .unwrap()
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("tracker-updater-updater-{}", name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+async fn updater(
+    url: &str,
+    config: Config,
+    second: CheckOutcome,
+    dir: &Path,
+    sender: mpsc::Sender<UpdaterEvent>,
+) -> Updater {
+    let client = Qbit::builder().endpoint(url).cookie("SID=test").build();
+    Updater::builder(config)
+        .client(client)
+        .providers(vec![Box::new(FakeProvider {
+            second,
+            dir: dir.to_path_buf(),
+        })])
+        .events(sender)
+        .build()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_updater_emits_events() {
+    let dir = temp_dir("events");
+    let calls = Calls::default();
+    let url = mock_qbit(calls.clone()).await;
+
+    let (sender, mut receiver) = mpsc::channel(32);
+    let config = test_config(&url, &dir, "");
+    let updater = updater(&url, config, CheckOutcome::Removed, &dir, sender).await;
+
+    // Запуск можно вынести в отдельную задачу
+    let updater = Arc::new(updater);
+    let report = tokio::spawn({
+        let updater = updater.clone();
+        async move { updater.run().await }
+    })
+    .await
+    .unwrap()
+    .unwrap();
+    drop(updater);
+
+    assert_eq!(report.checked, 2);
+    let calls = calls.lock().unwrap().clone();
+    assert_eq!(calls.len(), 1);
+    assert!(calls[0].starts_with("/api/v2/torrents/delete hashes=bbb"));
+
+    let mut events = Vec::new();
+    while let Some(event) = receiver.recv().await {
+        events.push(event);
+    }
+
+    let discovered: Vec<&str> = events
+        .iter()
+        .filter_map(|e| match e {
+            UpdaterEvent::TorrentDiscovered(t) => Some(t.hash.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(discovered, ["aaa", "bbb"]);
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, UpdaterEvent::StatsUpdated(s) if s.hash == "aaa" && s.seeders == 5)));
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, UpdaterEvent::Removed(t) if t.hash == "bbb")));
+    assert!(matches!(events.last(), Some(UpdaterEvent::RunFinished(_))));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_update_recheck_flags_missing_data() {
+    let dir = temp_dir("recheck");
+    let calls = Calls::default();
+    let url = mock_qbit(calls.clone()).await;
+
+    let extra = format!(
+        "[recheck]\nenabled = true\npoll_secs = 1\narchive_dir = \"{}/archive\"",
+        dir.display()
+    );
+    let config = test_config(&url, &dir, &extra);
+    let changed = CheckOutcome::Changed {
+        new_hash: "new".to_string(),
+        topic: None,
+    };
+    let (sender, mut receiver) = mpsc::channel(32);
+    let updater = updater(&url, config, changed, &dir, sender).await;
+
+    let report = updater.run().await.unwrap();
+    drop(updater);
+    let entry = report
+        .entries
+        .iter()
+        .find(|e| e.hash == "bbb")
+        .expect("нет записи об обновлении");
+    assert_eq!(entry.action, ReportAction::Updated);
+
+    // Проверку дожидаемся после всех действий: событие приходит после завершения обновления
+    let mut events = Vec::new();
+    while let Some(event) = receiver.recv().await {
+        events.push(event);
+    }
+    let finished = events
+        .iter()
+        .position(|e| matches!(e, UpdaterEvent::UpdateFinished { .. }))
+        .expect("нет события об обновлении");
+    let rechecked = events
+        .iter()
+        .position(
+            |e| matches!(e, UpdaterEvent::RecheckFinished { torrent, .. } if torrent.hash == "bbb"),
+        )
+        .expect("нет события о проверке");
+    assert!(finished < rechecked);
+
+    let recheck = entry.recheck.as_ref().expect("нет итога проверки");
+    assert!(recheck.finished && recheck.low && recheck.paused);
+    assert_eq!(recheck.present_bytes, 205);
+
+    // Старый .torrent сохранен для восстановления, временный файл удален
+    let archive = recheck.archive.as_deref().unwrap();
+    assert!(Path::new(archive).ends_with("archive/bbb.torrent"));
+    assert!(Path::new(archive).exists());
+    assert!(!dir.join("102.torrent").exists());
+
+    let calls = calls.lock().unwrap().clone();
+    let paths: Vec<&str> = calls.iter().map(|c| c.split(' ').next().unwrap()).collect();
+    assert_eq!(
+        paths,
+        [
+            "/api/v2/torrents/add",
+            "/api/v2/torrents/delete",
+            "/api/v2/torrents/stop"
+        ]
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_update_deferred_on_low_space() {
+    let dir = temp_dir("free-space");
+    let calls = Calls::default();
+    let url = mock_qbit(calls.clone()).await;
+
+    let config = test_config(&url, &dir, "[free_space]\nenabled = true");
+    let changed = CheckOutcome::Changed {
+        new_hash: "new".to_string(),
+        topic: None,
+    };
+    let (sender, _receiver) = mpsc::channel(32);
+    let updater = updater(&url, config, changed, &dir, sender).await;
+
+    let report = updater.run().await.unwrap();
+    let entry = report.entries.iter().find(|e| e.hash == "bbb").unwrap();
+    assert_eq!(entry.action, ReportAction::Deferred);
+    assert!(entry.error.as_deref().unwrap().contains("мало места"));
+
+    // Новый торрент не добавлялся, старый не удалялся
+    assert!(calls.lock().unwrap().is_empty());
+    assert!(!dir.join("102.torrent").exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_two_phase_update_keeps_old_torrent() {
+    let dir = temp_dir("two-phase");
+    let calls = Calls::default();
+    let url = mock_qbit(calls.clone()).await;
+
+    let two_phase = format!(
+        "[two_phase]\nenabled = true\nkeep_old = \"pause\"\nstate_path = \"{}/pending.json\"",
+        dir.display()
+    );
+    let changed = CheckOutcome::Changed {
+        new_hash: "new".to_string(),
+        topic: None,
+    };
+
+    // Первый запуск: новая версия добавлена, старый торрент остановлен, но не удален
+    let (sender, mut receiver) = mpsc::channel(32);
+    let config = test_config(&url, &dir, &two_phase);
+    let first = updater(&url, config, changed.clone(), &dir, sender).await;
+    let report = first.run().await.unwrap();
+    drop(first);
+
+    let entry = report.entries.iter().find(|e| e.hash == "bbb").unwrap();
+    assert_eq!(entry.action, ReportAction::Staged);
+    let paths: Vec<String> = calls.lock().unwrap().drain(..).collect();
+    assert!(paths[0].starts_with("/api/v2/torrents/add"));
+    assert!(paths[1].starts_with("/api/v2/torrents/stop hashes=bbb"));
+    assert_eq!(paths.len(), 2);
+    assert!(dir.join("pending.json").exists());
+
+    let mut staged = false;
+    while let Some(event) = receiver.recv().await {
+        staged |=
+            matches!(event, UpdaterEvent::UpdateStaged { torrent, .. } if torrent.hash == "bbb");
+    }
+    assert!(staged);
+
+    // Второй запуск: новая версия скачана на 10% — этого достаточно, старый торрент удаляется
+    // без файлов, а сам он больше не проверяется
+    let (sender, _receiver) = mpsc::channel(32);
+    let config = test_config(&url, &dir, &format!("{}\nmin_progress = 0.05", two_phase));
+    let second = updater(&url, config, changed, &dir, sender).await;
+    let report = second.run().await.unwrap();
+
+    assert_eq!(report.checked, 1);
+    let entry = report.entries.iter().find(|e| e.hash == "bbb").unwrap();
+    assert_eq!(entry.action, ReportAction::Updated);
+    assert_eq!(entry.new_hash.as_deref(), Some("new"));
+    let calls = calls.lock().unwrap().clone();
+    assert_eq!(
+        calls,
+        ["/api/v2/torrents/delete hashes=bbb&deleteFiles=false"]
+    );
+
+    let pending = std::fs::read_to_string(dir.join("pending.json")).unwrap();
+    assert!(!pending.contains("bbb"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_two_phase_add_paused_stops_new_torrent() {
+    let dir = temp_dir("two-phase-paused");
+    let calls = Calls::default();
+    let url = mock_qbit(calls.clone()).await;
+
+    // Старая версия раздается из той же папки: новая не должна запуститься
+    let two_phase = format!(
+        "[two_phase]\nenabled = true\nadd_paused = true\nstate_path = \"{}/pending.json\"",
+        dir.display()
+    );
+    let changed = CheckOutcome::Changed {
+        new_hash: "new".to_string(),
+        topic: None,
+    };
+    let (sender, _receiver) = mpsc::channel(32);
+    let config = test_config(&url, &dir, &two_phase);
+    let updater = updater(&url, config, changed, &dir, sender).await;
+    let report = updater.run().await.unwrap();
+
+    let entry = report.entries.iter().find(|e| e.hash == "bbb").unwrap();
+    assert_eq!(entry.action, ReportAction::Staged);
+    let calls = calls.lock().unwrap().clone();
+    assert_eq!(calls.len(), 2);
+    // Старое поле `paused` для qBittorrent 4 и отдельная остановка для qBittorrent 5
+    assert!(calls[0].starts_with("/api/v2/torrents/add"));
+    assert!(calls[0].contains("name=\"paused\"\r\n\r\ntrue"));
+    assert_eq!(calls[1], "/api/v2/torrents/stop hashes=new");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_two_phase_rolls_back_when_new_torrent_not_stopped() {
+    let dir = temp_dir("two-phase-stop-failed");
+    let calls = Calls::default();
+    let url = mock_qbit_failing(calls.clone(), "/stop").await;
+
+    let two_phase = format!(
+        "[two_phase]\nenabled = true\nadd_paused = true\nstate_path = \"{}/pending.json\"",
+        dir.display()
+    );
+    let changed = CheckOutcome::Changed {
+        new_hash: "new".to_string(),
+        topic: None,
+    };
+    let (sender, _receiver) = mpsc::channel(32);
+    let config = test_config(&url, &dir, &two_phase);
+    let updater = updater(&url, config, changed, &dir, sender).await;
+    let report = updater.run().await.unwrap();
+
+    // Новая версия убрана из клиента, старый торрент остался, обновление не сохранено
+    let entry = report.entries.iter().find(|e| e.hash == "bbb").unwrap();
+    assert_eq!(entry.action, ReportAction::Failed);
+    let calls = calls.lock().unwrap().clone();
+    let paths: Vec<&str> = calls.iter().map(|c| c.split(' ').next().unwrap()).collect();
+    assert_eq!(
+        paths,
+        [
+            "/api/v2/torrents/add",
+            "/api/v2/torrents/stop",
+            "/api/v2/torrents/delete"
+        ]
+    );
+    assert_eq!(
+        calls[2],
+        "/api/v2/torrents/delete hashes=new&deleteFiles=false"
+    );
+    assert!(!dir.join("pending.json").exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_two_phase_new_torrent_is_not_updated_again() {
+    let dir = temp_dir("two-phase-new-side");
+    let calls = Calls::default();
+    let url = mock_qbit(calls.clone()).await;
+
+    // bbb — новая версия незавершенного обновления aaa, которая еще не докачалась
+    let mut pending = PendingUpdates::default();
+    pending.insert(
+        "aaa".into(),
+        PendingUpdate {
+            tracker: "fake".into(),
+            name: "Первая".into(),
+            topic_id: Some(TopicId::new(101)),
+            new_hash: "bbb".into(),
+            old_paused: false,
+            delete_files: false,
+            added_at: 1,
+        },
+    );
+    pending.save(&dir.join("pending.json")).unwrap();
+
+    let two_phase = format!(
+        "[two_phase]\nenabled = true\nkeep_old = \"pause\"\nstate_path = \"{}/pending.json\"",
+        dir.display()
+    );
+    let changed = CheckOutcome::Changed {
+        new_hash: "new".to_string(),
+        topic: None,
+    };
+    let (sender, _receiver) = mpsc::channel(32);
+    let config = test_config(&url, &dir, &two_phase);
+    let updater = updater(&url, config, changed, &dir, sender).await;
+    let report = updater.run().await.unwrap();
+
+    // Тема снова обновилась, но bbb не трогаем, пока пара aaa → bbb не завершена
+    assert_eq!(report.checked, 0);
+    assert!(report.entries.iter().all(|e| e.hash != "bbb"));
+    assert!(calls.lock().unwrap().is_empty());
+    let pending = PendingUpdates::load(&dir.join("pending.json")).unwrap();
+    assert!(pending.contains("aaa"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// .torrent раздачи `rel` из файлов `same` и `changed` с частями по `piece` байт;
+/// `pieces` — буква хеша каждой части
+fn release_torrent(same: u64, changed: u64, piece: u64, pieces: &str) -> Vec<u8> {
+    let mut data = format!(
+        "d4:infod5:filesld6:lengthi{}e4:pathl4:sameeed6:lengthi{}e4:pathl7:changedeee\
+         4:name3:rel12:piece lengthi{}e6:pieces{}:",
+        same,
+        changed,
+        piece,
+        pieces.len() * 20
+    );
+    for c in pieces.chars() {
+        data += &c.to_string().repeat(20);
+    }
+    data += "ee";
+    data.into_bytes()
+}
+
+/// Файлы раздачи `rel` в qBittorrent (скачаны полностью)
+fn release_files(same: u64, changed: u64) -> Value {
+    json!([
+        {"index": 0, "name": "rel/same", "size": same, "progress": 1.0, "priority": 1},
+        {"index": 1, "name": "rel/changed", "size": changed, "progress": 1.0, "priority": 1}
+    ])
+}
+
+#[tokio::test]
+async fn test_update_keeps_previous_version() {
+    let dir = temp_dir("keep-previous");
+    let calls = Calls::default();
+    // Размер файлов не меняется, но у changed другое содержимое (хеш второй части)
+    let url = mock_qbit_with_torrent(
+        calls.clone(),
+        release_files(4, 4),
+        release_torrent(4, 4, 4, "AB"),
+    )
+    .await;
+    std::fs::write(dir.join("metainfo.torrent"), release_torrent(4, 4, 4, "AC")).unwrap();
+
+    std::fs::create_dir_all(dir.join("data/rel")).unwrap();
+    std::fs::write(dir.join("data/rel/same"), b"same").unwrap();
+    std::fs::write(dir.join("data/rel/changed"), b"old!").unwrap();
+
+    let extra = format!(
+        "[keep_previous]\nenabled = true\nmode = \"move\"\ndir = \"{dir}/versions\"\n\
+         remote_prefix = \"/data\"\nlocal_prefix = \"{dir}/data\"",
+        dir = dir.display()
+    );
+    let config = test_config(&url, &dir, &extra);
+    let changed = CheckOutcome::Changed {
+        new_hash: "new".to_string(),
+        topic: None,
+    };
+    let (sender, _receiver) = mpsc::channel(32);
+    let updater = updater(&url, config, changed, &dir, sender).await;
+
+    let report = updater.run().await.unwrap();
+    let entry = report.entries.iter().find(|e| e.hash == "bbb").unwrap();
+    assert_eq!(entry.action, ReportAction::Updated);
+
+    // Сохранен только измененный файл, совпадающий по хешам остался на месте
+    let preserved = Path::new(entry.preserved.as_deref().expect("версия не сохранена"));
+    assert!(preserved.starts_with(dir.join("versions/102")));
+    assert_eq!(
+        std::fs::read(preserved.join("rel/changed")).unwrap(),
+        b"old!"
+    );
+    assert!(!preserved.join("rel/same").exists());
+    assert!(preserved.join("version.json").exists());
+    assert!(!dir.join("data/rel/changed").exists());
+    assert_eq!(std::fs::read(dir.join("data/rel/same")).unwrap(), b"same");
+
+    let calls = calls.lock().unwrap().clone();
+    let paths: Vec<&str> = calls.iter().map(|c| c.split(' ').next().unwrap()).collect();
+    assert_eq!(paths, ["/api/v2/torrents/add", "/api/v2/torrents/delete"]);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_free_space_counts_preserved_files() {
+    // Свободно 1 000 000 байт, новая версия ничего не докачивает, но изменившийся файл
+    // (1 200 000 байт) копируется в папку версий или, при переносе, скачивается заново
+    for mode in ["copy", "move"] {
+        let dir = temp_dir(&format!("free-space-keep-{}", mode));
+        let calls = Calls::default();
+        let url = mock_qbit_with_torrent(
+            calls.clone(),
+            release_files(600_000, 1_200_000),
+            release_torrent(600_000, 1_200_000, 600_000, "ABB"),
+        )
+        .await;
+        std::fs::write(
+            dir.join("metainfo.torrent"),
+            release_torrent(600_000, 1_200_000, 600_000, "ACC"),
+        )
+        .unwrap();
+
+        let extra = format!(
+            "[free_space]\nenabled = true\n\n[keep_previous]\nenabled = true\nmode = \"{mode}\"\n\
+             dir = \"{dir}/versions\"\nremote_prefix = \"/data\"\nlocal_prefix = \"{dir}/data\"",
+            dir = dir.display()
+        );
+        let config = test_config(&url, &dir, &extra);
+        let changed = CheckOutcome::Changed {
+            new_hash: "new".to_string(),
+            topic: None,
+        };
+        let (sender, _receiver) = mpsc::channel(32);
+        let updater = updater(&url, config, changed, &dir, sender).await;
+
+        let report = updater.run().await.unwrap();
+        let entry = report.entries.iter().find(|e| e.hash == "bbb").unwrap();
+        assert_eq!(entry.action, ReportAction::Deferred, "mode = {}", mode);
+        assert!(calls.lock().unwrap().is_empty());
+        assert!(!dir.join("versions").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
+
+#[tokio::test]
+async fn test_apply_skips_pending_update() {
+    let dir = temp_dir("apply-pending");
+    let calls = Calls::default();
+    let url = mock_qbit(calls.clone()).await;
+
+    let two_phase = format!(
+        "[two_phase]\nenabled = true\nkeep_old = \"pause\"\nstate_path = \"{}/pending.json\"",
+        dir.display()
+    );
+    let changed = CheckOutcome::Changed {
+        new_hash: "new".to_string(),
+        topic: None,
+    };
+    let (sender, _receiver) = mpsc::channel(32);
+    let config = test_config(&url, &dir, &two_phase);
+    let updater = updater(&url, config, changed, &dir, sender).await;
+
+    // План составлен до того, как обновление было начато обычным запуском
+    let plan_path = dir.join("plan.json");
+    updater.plan(&plan_path).await.unwrap();
+    updater.run().await.unwrap();
+    calls.lock().unwrap().clear();
+
+    let report = updater
+        .apply(Plan::load(&plan_path).unwrap())
+        .await
+        .unwrap();
+    let entry = report.entries.iter().find(|e| e.hash == "bbb").unwrap();
+    assert_eq!(entry.action, ReportAction::Refused);
+    assert!(entry.error.as_deref().unwrap().contains("двухфазного"));
+    assert!(calls.lock().unwrap().is_empty());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_failed_update_restores_moved_files() {
+    let dir = temp_dir("keep-previous-undo");
+    let calls = Calls::default();
+    let url = mock_qbit_failing(calls.clone(), "/add").await;
+
+    std::fs::create_dir_all(dir.join("data")).unwrap();
+    std::fs::write(dir.join("data/new"), b"old release").unwrap();
+
+    let extra = format!(
+        "[keep_previous]\nenabled = true\nmode = \"move\"\ndir = \"{dir}/versions\"\n\
+         remote_prefix = \"/data\"\nlocal_prefix = \"{dir}/data\"",
+        dir = dir.display()
+    );
+    let config = test_config(&url, &dir, &extra);
+    let changed = CheckOutcome::Changed {
+        new_hash: "new".to_string(),
+        topic: None,
+    };
+    let (sender, _receiver) = mpsc::channel(32);
+    let updater = updater(&url, config, changed, &dir, sender).await;
+
+    let report = updater.run().await.unwrap();
+    let entry = report.entries.iter().find(|e| e.hash == "bbb").unwrap();
+    assert_eq!(entry.action, ReportAction::Failed);
+
+    // Новая версия не добавлена — файл старой возвращен на место, папки версии нет
+    assert_eq!(std::fs::read(dir.join("data/new")).unwrap(), b"old release");
+    assert!(std::fs::read_dir(dir.join("versions/102"))
+        .map(|mut d| d.next().is_none())
+        .unwrap_or(true));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}